@@ -0,0 +1,221 @@
+//! Insert, contains and build benchmarks.
+//!
+//! Run with `cargo bench`. `cargo test --benches` runs every benchmark once as a smoke test.
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use fastbloom::{AtomicBloomFilter, BloomFilter};
+
+const NUM_BITS: [usize; 3] = [1 << 12, 1 << 16, 1 << 22];
+const NUM_HASHES: [u32; 3] = [1, 4, 8];
+const BATCH: u64 = 1000;
+
+fn insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("insert");
+    group.throughput(Throughput::Elements(BATCH));
+    for num_bits in NUM_BITS {
+        for num_hashes in NUM_HASHES {
+            let id = BenchmarkId::new(format!("{num_hashes} hashes"), num_bits);
+            let mut filter = BloomFilter::with_num_bits(num_bits)
+                .seed(&1)
+                .hashes(num_hashes);
+            group.bench_with_input(id, &num_bits, |b, _| {
+                b.iter(|| {
+                    for x in 0..BATCH {
+                        filter.insert(black_box(&x));
+                    }
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn contains(c: &mut Criterion) {
+    for (name, offset) in [("contains member", 0), ("contains non-member", BATCH)] {
+        let mut group = c.benchmark_group(name);
+        group.throughput(Throughput::Elements(BATCH));
+        for num_bits in NUM_BITS {
+            for num_hashes in NUM_HASHES {
+                let id = BenchmarkId::new(format!("{num_hashes} hashes"), num_bits);
+                let mut filter = BloomFilter::with_num_bits(num_bits)
+                    .seed(&1)
+                    .hashes(num_hashes);
+                filter.extend(0..BATCH);
+                group.bench_with_input(id, &num_bits, |b, _| {
+                    b.iter(|| {
+                        for x in offset..offset + BATCH {
+                            black_box(filter.contains(black_box(&x)));
+                        }
+                    })
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Probes pre-computed hashes, so the cost of hashing items doesn't hide the single-hash path's savings.
+fn single_hash(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single hash");
+    group.throughput(Throughput::Elements(BATCH));
+    let hashes: Vec<u64> = (0..BATCH)
+        .map(|x| x.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+        .collect();
+    for num_hashes in [1, 2] {
+        let mut filter = BloomFilter::with_num_bits(1 << 22)
+            .seed(&1)
+            .hashes(num_hashes);
+        group.bench_function(BenchmarkId::new("insert_hash", num_hashes), |b| {
+            b.iter(|| {
+                for &hash in &hashes {
+                    filter.insert_hash(black_box(hash));
+                }
+            })
+        });
+        group.bench_function(BenchmarkId::new("contains_hash", num_hashes), |b| {
+            b.iter(|| {
+                for &hash in &hashes {
+                    black_box(filter.contains_hash(black_box(hash)));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Compares `IntBloomFilter` to a `BloomFilter` of `u64`s at the same false positive rate.
+fn int_keys(c: &mut Criterion) {
+    let mut group = c.benchmark_group("int keys");
+    group.throughput(Throughput::Elements(BATCH));
+    let num_items = 100_000;
+    let mut int = BloomFilter::with_false_pos(0.001)
+        .seed(&1)
+        .int_expected_items(num_items);
+    let mut generic = BloomFilter::with_false_pos(0.001)
+        .seed(&1)
+        .expected_items(num_items);
+    group.bench_function("IntBloomFilter insert", |b| {
+        b.iter(|| {
+            for x in 0..BATCH {
+                int.insert(black_box(x));
+            }
+        })
+    });
+    group.bench_function("BloomFilter insert", |b| {
+        b.iter(|| {
+            for x in 0..BATCH {
+                generic.insert(black_box(&x));
+            }
+        })
+    });
+    group.bench_function("IntBloomFilter contains", |b| {
+        b.iter(|| {
+            for x in BATCH..2 * BATCH {
+                black_box(int.contains(black_box(x)));
+            }
+        })
+    });
+    group.bench_function("BloomFilter contains", |b| {
+        b.iter(|| {
+            for x in BATCH..2 * BATCH {
+                black_box(generic.contains(black_box(&x)));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build");
+    for num_items in [1_000u64, 100_000] {
+        let items: Vec<u64> = (0..num_items).collect();
+        group.throughput(Throughput::Elements(num_items));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_items),
+            &items,
+            |b, items| {
+                b.iter(|| {
+                    BloomFilter::with_false_pos(0.001)
+                        .seed(&1)
+                        .items(items.iter())
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn atomic_contention(c: &mut Criterion) {
+    let mut group = c.benchmark_group("atomic insert");
+    for num_threads in [1, 2, 4] {
+        group.throughput(Throughput::Elements(BATCH * num_threads));
+        let filter = AtomicBloomFilter::with_num_bits(1 << 16).seed(&1).hashes(4);
+        group.bench_with_input(
+            BenchmarkId::new("threads", num_threads),
+            &num_threads,
+            |b, &num_threads| {
+                b.iter(|| {
+                    std::thread::scope(|s| {
+                        for t in 0..num_threads {
+                            let filter = &filter;
+                            s.spawn(move || {
+                                for x in t * BATCH..(t + 1) * BATCH {
+                                    filter.insert(black_box(&x));
+                                }
+                            });
+                        }
+                    })
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+#[cfg(feature = "rayon")]
+fn par_extend(c: &mut Criterion) {
+    use rayon::prelude::*;
+
+    let mut group = c.benchmark_group("par_extend");
+    let num_items = 1_000_000u64;
+    group.throughput(Throughput::Elements(num_items));
+    group.bench_function("sequential", |b| {
+        let filter = AtomicBloomFilter::with_false_pos(0.001)
+            .seed(&1)
+            .expected_items(num_items as usize);
+        b.iter(|| {
+            for x in 0..num_items {
+                filter.insert(black_box(&x));
+            }
+        })
+    });
+    group.bench_function("parallel", |b| {
+        let filter = AtomicBloomFilter::with_false_pos(0.001)
+            .seed(&1)
+            .expected_items(num_items as usize);
+        b.iter(|| filter.par_extend((0..num_items).into_par_iter().map(black_box)))
+    });
+    group.finish();
+}
+
+#[cfg(not(feature = "rayon"))]
+criterion_group!(
+    benches,
+    insert,
+    contains,
+    single_hash,
+    int_keys,
+    build,
+    atomic_contention
+);
+#[cfg(feature = "rayon")]
+criterion_group!(
+    benches,
+    insert,
+    contains,
+    single_hash,
+    int_keys,
+    build,
+    atomic_contention,
+    par_extend
+);
+criterion_main!(benches);
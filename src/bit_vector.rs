@@ -1,10 +1,11 @@
 use crate::AtomicU64;
 use alloc::{boxed::Box, vec::Vec};
-use portable_atomic::Ordering::Relaxed;
+use core::sync::atomic::Ordering::{self, Relaxed};
 
 /// A bit vector partitioned in to `u64` blocks.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub(crate) struct BitVec {
     bits: Box<[u64]>,
 }
@@ -12,6 +13,7 @@ pub(crate) struct BitVec {
 /// A bit vector partitioned in to `u64` blocks.
 #[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub(crate) struct AtomicBitVec {
     bits: Box<[AtomicU64]>,
 }
@@ -34,6 +36,20 @@ macro_rules! impl_bitvec {
                 &self.bits
             }
 
+            #[inline(always)]
+            pub(crate) fn is_empty(&self) -> bool {
+                self.bits.iter().map(Self::fetch).all(|x| x == 0)
+            }
+
+            #[inline]
+            pub(crate) fn into_vec(self) -> Vec<u64> {
+                self.bits
+                    .into_vec()
+                    .into_iter()
+                    .map(|x| Self::fetch(&x))
+                    .collect()
+            }
+
             #[inline(always)]
             pub(crate) fn iter(&self) -> impl Iterator<Item = u64> + '_ {
                 self.bits.iter().map(Self::fetch)
@@ -70,6 +86,7 @@ impl_bitvec!(BitVec, u64);
 impl_bitvec!(AtomicBitVec, AtomicU64);
 
 impl BitVec {
+    #[allow(clippy::new_ret_no_self)]
     #[inline(always)]
     fn new(x: u64) -> u64 {
         x
@@ -103,6 +120,18 @@ impl BitVec {
         }
     }
 
+    /// Unions `other` into `self`, returning the number of bits that were newly set.
+    #[inline(always)]
+    pub(crate) fn union_counting(&mut self, other: &BitVec) -> usize {
+        assert_eq!(self.len(), other.len(), "expected same length");
+        let mut newly_set = 0;
+        for (x, y) in self.bits.iter_mut().zip(other.bits.iter()) {
+            newly_set += (y & !*x).count_ones() as usize;
+            *x |= y;
+        }
+        newly_set
+    }
+
     #[inline(always)]
     pub(crate) fn intersect(&mut self, other: &BitVec) {
         assert_eq!(self.len(), other.len(), "expected same length");
@@ -110,9 +139,35 @@ impl BitVec {
             self.bits[i] &= other.bits[i];
         }
     }
+
+    #[inline(always)]
+    pub(crate) fn union_atomic(&mut self, other: &AtomicBitVec) {
+        assert_eq!(self.len(), other.len(), "expected same length");
+        for (x, y) in self.bits.iter_mut().zip(other.iter()) {
+            *x |= y;
+        }
+    }
+
+    #[inline]
+    pub(crate) fn count_ones(&self) -> usize {
+        self.iter().map(|x| x.count_ones() as usize).sum()
+    }
+
+    #[inline]
+    pub(crate) fn into_atomic(self) -> AtomicBitVec {
+        AtomicBitVec {
+            bits: self
+                .bits
+                .into_vec()
+                .into_iter()
+                .map(AtomicBitVec::new)
+                .collect(),
+        }
+    }
 }
 
 impl AtomicBitVec {
+    #[allow(clippy::new_ret_no_self)]
     #[inline]
     fn new(x: u64) -> AtomicU64 {
         AtomicU64::new(x)
@@ -123,16 +178,51 @@ impl AtomicBitVec {
         x.load(Relaxed)
     }
 
+    #[cfg(not(feature = "rayon"))]
+    #[inline]
+    pub(crate) fn count_ones(&self) -> usize {
+        self.iter().map(|x| x.count_ones() as usize).sum()
+    }
+
+    /// Counts the words in parallel: large atomic bit vectors are typically shared and monitored while in use.
+    /// Small bit vectors are counted on the current thread, since splitting them isn't worth the overhead.
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub(crate) fn count_ones(&self) -> usize {
+        use rayon::prelude::*;
+        self.bits
+            .par_iter()
+            .with_min_len(1 << 12)
+            .map(|x| Self::fetch(x).count_ones() as usize)
+            .sum()
+    }
+
     #[inline]
     pub(crate) fn set(&self, index: usize) -> bool {
+        self.set_ordered(index, Relaxed)
+    }
+
+    #[inline]
+    pub(crate) fn set_ordered(&self, index: usize, ordering: Ordering) -> bool {
+        let (index, bit) = coord(index);
+        self.bits[index].fetch_or(bit, ordering) & bit > 0
+    }
+
+    #[inline]
+    pub(crate) fn check_ordered(&self, index: usize, ordering: Ordering) -> bool {
         let (index, bit) = coord(index);
-        self.bits[index].fetch_or(bit, Relaxed) & bit > 0
+        self.bits[index].load(ordering) & bit > 0
     }
 
     #[inline]
     pub(crate) fn clear(&self) {
+        self.clear_ordered(Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn clear_ordered(&self, ordering: Ordering) {
         for i in 0..self.len() {
-            self.bits[i].store(0, Relaxed);
+            self.bits[i].store(0, ordering);
         }
     }
 
@@ -145,6 +235,19 @@ impl AtomicBitVec {
         }
     }
 
+    /// Unions `other` into `self`, returning the number of bits that were newly set by this call.
+    #[inline]
+    pub(crate) fn union_counting(&self, other: &AtomicBitVec) -> usize {
+        assert_eq!(self.len(), other.len(), "expected same length");
+        let mut newly_set = 0;
+        for i in 0..self.len() {
+            let x = other.bits[i].load(Relaxed);
+            let prev = self.bits[i].fetch_or(x, Relaxed);
+            newly_set += (x & !prev).count_ones() as usize;
+        }
+        newly_set
+    }
+
     #[inline]
     pub(crate) fn intersect(&self, other: &AtomicBitVec) {
         assert_eq!(self.len(), other.len(), "expected same length");
@@ -153,6 +256,26 @@ impl AtomicBitVec {
             self.bits[i].fetch_and(x, Relaxed);
         }
     }
+
+    #[inline]
+    pub(crate) fn into_standard(self) -> BitVec {
+        BitVec {
+            bits: self
+                .bits
+                .into_vec()
+                .into_iter()
+                .map(AtomicU64::into_inner)
+                .collect(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn union_standard(&self, other: &BitVec) {
+        assert_eq!(self.len(), other.len(), "expected same length");
+        for i in 0..self.len() {
+            self.bits[i].fetch_or(other.bits[i], Relaxed);
+        }
+    }
 }
 
 impl Clone for AtomicBitVec {
@@ -162,7 +285,7 @@ impl Clone for AtomicBitVec {
 }
 
 #[inline]
-fn coord(index: usize) -> (usize, u64) {
+pub(crate) fn coord(index: usize) -> (usize, u64) {
     (index >> 6, 1u64 << (index & 0b111111))
 }
 
@@ -207,3 +330,18 @@ macro_rules! impl_tests {
 
 impl_tests!(non_atomic, BitVec);
 impl_tests!(atomic, AtomicBitVec);
+
+#[cfg(all(test, feature = "rayon", not(feature = "loom")))]
+mod rayon_tests {
+    use super::*;
+
+    #[test]
+    fn par_count_ones_matches_sequential() {
+        let mut rng = fastrand::Rng::with_seed(519);
+        for len in [1, 100, 1 << 12, (1 << 16) + 7] {
+            let vec: AtomicBitVec = (0..len).map(|_| rng.u64(..)).collect();
+            let sequential: usize = vec.iter().map(|x| x.count_ones() as usize).sum();
+            assert_eq!(vec.count_ones(), sequential);
+        }
+    }
+}
@@ -0,0 +1,479 @@
+use crate::{
+    index, math::*, optimal_hashes_capped, optimal_size, BloomFilter, BuildHasher, BuilderWithBits,
+    BuilderWithFalsePositiveRate, DefaultHasher, DoubleHasher,
+};
+use core::{cmp::max, hash::Hash};
+
+/// The number of bits in a block: one 64-byte cache line.
+const BLOCK_BITS: usize = 512;
+/// Bloom filters with fewer blocks than this probe their whole bit vector instead of a single block.
+///
+/// Blocking raises the false positive rate in exchange for touching a single cache line per lookup. Below 8 blocks
+/// (4096 bits, 512 bytes), the whole bit vector is only a few cache lines that stay cached while the Bloom filter is
+/// in use, so the exchange isn't worth it. With very few blocks, the Poisson model of [`blocked_false_pos`] also no
+/// longer describes how items spread over blocks.
+const MIN_BLOCKS: usize = 8;
+
+/// A Bloom filter that confines all of an item's bits to a single 512-bit block, i.e. one cache line.
+///
+/// A [`BloomFilter`] sets or checks `num_hashes` bits anywhere in the bit vector, which for large Bloom filters means
+/// up to `num_hashes` cache misses per lookup. [`BlockedBloomFilter`] picks one block per item and only probes bits
+/// within it, so each lookup touches a single cache line. The trade-off is a slightly higher false positive rate:
+/// some blocks receive more items than others, and the fuller blocks produce more false positives than the
+/// evenly spread bits of a [`BloomFilter`] of the same size. See [`expected_false_pos`](Self::expected_false_pos).
+///
+/// A [`BlockedBloomFilter`] is built with [`BloomFilter`]'s builders, ending in
+/// [`blocked_expected_items`](BuilderWithFalsePositiveRate::blocked_expected_items) or
+/// [`blocked_hashes`](BuilderWithBits::blocked_hashes), or with
+/// [`BlockedBloomFilter::with_num_blocks`]. The number of bits should be a multiple of 512;
+/// bits past the last full block are unused.
+///
+/// Bloom filters with fewer than 8 blocks (4096 bits) are not blocked: they probe their whole bit vector, exactly like a
+/// [`BloomFilter`]. Blocking only pays off once the bit vector spans many cache lines, while it raises the false positive
+/// rate at any size.
+///
+/// # Examples
+/// ```
+/// use fastbloom::BloomFilter;
+///
+/// let mut filter = BloomFilter::with_false_pos(0.001).blocked_expected_items(1000);
+/// filter.insert(&42);
+/// filter.extend(["🦀"]);
+/// assert!(filter.contains(&42));
+/// assert!(filter.contains("🦀"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BlockedBloomFilter<S = DefaultHasher> {
+    bloom: BloomFilter<S>,
+    num_blocks: usize,
+}
+
+impl BlockedBloomFilter {
+    /// Creates a builder instance to construct a [`BlockedBloomFilter`] with `num_blocks` 512-bit blocks,
+    /// i.e. `num_blocks * 512` bits. Finish it with [`blocked_hashes`](BuilderWithBits::blocked_hashes).
+    ///
+    /// With fewer than 8 blocks, the Bloom filter probes its whole bit vector rather than a single block.
+    ///
+    /// # Panics
+    /// Panics if `num_blocks` is 0, or the number of bits is greater than `usize::MAX - 63`.
+    ///
+    /// # Examples
+    /// ```
+    /// use fastbloom::BlockedBloomFilter;
+    ///
+    /// let filter = BlockedBloomFilter::with_num_blocks(16).blocked_hashes(6);
+    /// assert_eq!(filter.num_bits(), 8192);
+    /// ```
+    pub fn with_num_blocks(num_blocks: usize) -> BuilderWithBits {
+        BloomFilter::with_num_bits(num_blocks.saturating_mul(BLOCK_BITS))
+    }
+}
+
+impl<S: BuildHasher> BuilderWithBits<S> {
+    /// "Consumes" this builder, using the provided `num_hashes` to return an empty [`BlockedBloomFilter`].
+    ///
+    /// Note: if `num_hashes` is 0, it is treated as 1.
+    ///
+    /// # Examples
+    /// ```
+    /// use fastbloom::BloomFilter;
+    ///
+    /// let filter = BloomFilter::with_num_bits(4096).seed(&1).blocked_hashes(6);
+    /// assert_eq!(filter.num_blocks(), 8);
+    /// ```
+    pub fn blocked_hashes(self, num_hashes: u32) -> BlockedBloomFilter<S> {
+        BlockedBloomFilter::new(self.hashes(num_hashes))
+    }
+}
+
+impl<S: BuildHasher> BuilderWithFalsePositiveRate<S> {
+    /// "Consumes" this builder, using the provided `expected_items` to return an empty [`BlockedBloomFilter`]
+    /// whose [`expected_false_pos`](BlockedBloomFilter::expected_false_pos) for `expected_items` is at most the
+    /// desired false positive rate.
+    ///
+    /// A [`BlockedBloomFilter`] needs more bits than a [`BloomFilter`] for the same false positive rate, so blocks are
+    /// added to the size [`expected_items`](Self::expected_items) would choose until the target is met.
+    ///
+    /// Note: `expected_items` will internally be set to 1 if 0 is specified.
+    ///
+    /// # Examples
+    /// ```
+    /// use fastbloom::BloomFilter;
+    ///
+    /// let filter = BloomFilter::with_false_pos(0.001).blocked_expected_items(1000);
+    /// assert!(filter.expected_false_pos(1000) <= 0.001);
+    /// ```
+    pub fn blocked_expected_items(self, expected_items: usize) -> BlockedBloomFilter<S> {
+        let expected_items = max(1, expected_items);
+        let num_bits = optimal_size(expected_items, self.desired_fp_rate);
+        if num_bits < MIN_BLOCKS * BLOCK_BITS {
+            return BlockedBloomFilter::new(self.expected_items(expected_items));
+        }
+        let mut num_blocks = (num_bits + BLOCK_BITS - 1) / BLOCK_BITS;
+        let num_hashes = loop {
+            let num_hashes = optimal_hashes_capped(
+                num_blocks * BLOCK_BITS,
+                expected_items,
+                crate::math::DEFAULT_MAX_HASHES,
+            );
+            if blocked_false_pos(num_blocks, num_hashes, expected_items) <= self.desired_fp_rate {
+                break num_hashes;
+            }
+            num_blocks += max(1, num_blocks / 32);
+        };
+        BlockedBloomFilter::new(
+            BloomFilter::new_builder(num_blocks * BLOCK_BITS)
+                .hasher(self.hasher)
+                .hashes(num_hashes),
+        )
+    }
+}
+
+impl<S: BuildHasher> BlockedBloomFilter<S> {
+    fn new(bloom: BloomFilter<S>) -> Self {
+        let num_blocks = bloom.num_bits() / BLOCK_BITS;
+        Self { bloom, num_blocks }
+    }
+
+    /// Inserts an element into the Bloom filter.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
+    /// `false` otherwise.
+    #[inline]
+    pub fn insert(&mut self, val: &(impl Hash + ?Sized)) -> bool {
+        self.insert_hash(self.bloom.source_hash(val))
+    }
+
+    /// Inserts the hash of an element into the Bloom filter.
+    /// That is the element is pre-hashed and all subsequent hashes are derived from this "source" hash.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
+    /// `false` otherwise.
+    #[inline]
+    pub fn insert_hash(&mut self, hash: u64) -> bool {
+        if !self.is_blocked() {
+            return self.bloom.insert_hash(hash);
+        }
+        let block = self.block(hash);
+        let mut hasher = DoubleHasher::new(hash);
+        let mut previously_contained = true;
+        for _ in 0..self.num_hashes() {
+            previously_contained &= self.bloom.bits.set(block + offset(hasher.next()));
+        }
+        previously_contained
+    }
+
+    /// Checks if an element is possibly in the Bloom filter.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
+    #[inline]
+    pub fn contains(&self, val: &(impl Hash + ?Sized)) -> bool {
+        self.contains_hash(self.bloom.source_hash(val))
+    }
+
+    /// Checks if the hash of an element is possibly in the Bloom filter.
+    /// That is the element is pre-hashed and all subsequent hashes are derived from this "source" hash.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
+    #[inline]
+    pub fn contains_hash(&self, hash: u64) -> bool {
+        if !self.is_blocked() {
+            return self.bloom.contains_hash(hash);
+        }
+        let block = self.block(hash);
+        // Nothing was inserted into an empty block, so skip probing it. This speeds up lookups in lightly filled filters.
+        self.block_words(block).iter().any(|&word| word != 0) && self.probe(block, hash)
+    }
+
+    /// Checks the bits of `hash` within the block starting at bit `block`.
+    #[inline]
+    fn probe(&self, block: usize, hash: u64) -> bool {
+        let mut hasher = DoubleHasher::new(hash);
+        (0..self.num_hashes()).all(|_| self.bloom.bits.check(block + offset(hasher.next())))
+    }
+
+    /// Returns the words of the block starting at bit `block`.
+    #[inline]
+    fn block_words(&self, block: usize) -> &[u64] {
+        let start = block / 64;
+        &self.as_slice()[start..start + BLOCK_BITS / 64]
+    }
+
+    /// Returns `false` if this Bloom filter has too few blocks to be blocked, and probes its whole bit vector instead.
+    #[inline]
+    fn is_blocked(&self) -> bool {
+        self.num_blocks >= MIN_BLOCKS
+    }
+
+    /// Returns the index of the first bit of the block for `hash`.
+    #[inline]
+    fn block(&self, hash: u64) -> usize {
+        index(self.num_blocks, hash) * BLOCK_BITS
+    }
+
+    /// Returns the number of hashes per item.
+    #[inline]
+    pub fn num_hashes(&self) -> u32 {
+        self.bloom.num_hashes()
+    }
+
+    /// Returns the total number of in-memory bits supporting the Bloom filter, including any unused bits past the last block.
+    #[inline]
+    pub fn num_bits(&self) -> usize {
+        self.bloom.num_bits()
+    }
+
+    /// Returns the number of bits per block, 512, i.e. one 64-byte cache line.
+    ///
+    /// See [`recommend_block_bits`](crate::math::recommend_block_bits) for the block size matching other cache lines.
+    #[inline]
+    pub fn block_bits(&self) -> usize {
+        BLOCK_BITS
+    }
+
+    /// Returns the number of 512-bit blocks.
+    ///
+    /// Bloom filters with fewer than 8 blocks probe their whole bit vector rather than a single block.
+    #[inline]
+    pub fn num_blocks(&self) -> usize {
+        self.num_blocks
+    }
+
+    /// Returns the underlying slice of this Bloom filter's bit contents.
+    #[inline]
+    pub fn as_slice(&self) -> &[u64] {
+        self.bloom.as_slice()
+    }
+
+    /// Clear all of the bits in the Bloom filter, removing all items.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.bloom.clear();
+    }
+
+    /// Returns the expected false positive rate of this Bloom filter containing `num_items`.
+    ///
+    /// Items are spread over blocks following a Poisson distribution, so this is the false positive rate of a
+    /// single 512-bit Bloom filter, weighted by the probability of a block containing each number of items.
+    /// Bloom filters with fewer than 8 blocks are not blocked, and have the false positive rate of a [`BloomFilter`].
+    pub fn expected_false_pos(&self, num_items: usize) -> f64 {
+        match self.is_blocked() {
+            true => blocked_false_pos(self.num_blocks, self.num_hashes(), num_items),
+            false => self.bloom.expected_false_pos(num_items),
+        }
+    }
+}
+
+impl<T: Hash, S: BuildHasher> Extend<T> for BlockedBloomFilter<S> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.insert(&val);
+        }
+    }
+}
+
+/// Returns the expected false positive rate of a [`BlockedBloomFilter`], see [`BlockedBloomFilter::expected_false_pos`].
+fn blocked_false_pos(num_blocks: usize, num_hashes: u32, num_items: usize) -> f64 {
+    let per_block = num_items as f64 / num_blocks as f64;
+    // Blocks with more items than this are too rare to matter.
+    let max_items = (per_block + 10.0 * pow(per_block, 0.5) + 10.0) as usize;
+    let mut ln_prob = -per_block;
+    let mut fp = 0.0;
+    for items_in_block in 0..=max_items {
+        if items_in_block > 0 {
+            ln_prob += ln(per_block) - ln(items_in_block as f64);
+        }
+        let density = crate::expected_density(num_hashes, BLOCK_BITS, items_in_block);
+        fp += pow(core::f64::consts::E, ln_prob) * crate::expected_false_pos(num_hashes, density);
+    }
+    fp
+}
+
+/// Returns a bit offset within a block from the top bits of `hash`.
+#[inline]
+fn offset(hash: u64) -> usize {
+    (hash >> (u64::BITS - BLOCK_BITS.trailing_zeros())) as usize
+}
+
+#[cfg(not(feature = "loom"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn no_false_negatives() {
+        let mut filter = BloomFilter::with_num_bits(1 << 14).blocked_hashes(6);
+        for x in 0..2000u64 {
+            filter.insert(&x);
+        }
+        assert!((0..2000u64).all(|x| filter.contains(&x)));
+    }
+
+    #[test]
+    fn probes_single_block() {
+        let mut filter = BloomFilter::with_num_bits(1 << 14).blocked_hashes(8);
+        for x in 0..100u64 {
+            filter.clear();
+            filter.insert(&x);
+            let words: Vec<usize> = (0..filter.as_slice().len())
+                .filter(|&i| filter.as_slice()[i] != 0)
+                .collect();
+            assert_eq!(
+                words.first().unwrap() / (BLOCK_BITS / 64),
+                words.last().unwrap() / (BLOCK_BITS / 64)
+            );
+        }
+    }
+
+    #[test]
+    fn matches_theoretical_fp() {
+        for (num_bits, num_hashes, num_items) in
+            [(1 << 14, 4, 1000), (1 << 16, 7, 5000), (1 << 16, 3, 20000)]
+        {
+            let expected_standard = BloomFilter::with_num_bits(num_bits)
+                .hashes(num_hashes)
+                .expected_false_pos(num_items);
+            let mut filter = BloomFilter::with_num_bits(num_bits)
+                .seed(&1)
+                .blocked_hashes(num_hashes);
+            filter.extend(0..num_items as u64);
+
+            let trials = 100_000;
+            let false_positives = (num_items as u64..(num_items + trials) as u64)
+                .filter(|x| filter.contains(x))
+                .count();
+            let sample_fp = false_positives as f64 / trials as f64;
+            let expected = filter.expected_false_pos(num_items);
+            assert!(expected > expected_standard);
+            let err = (sample_fp - expected).abs() / expected;
+            assert!(err < 0.2, "sample fp: {sample_fp}, expected: {expected}");
+        }
+    }
+
+    #[test]
+    fn block_bits_match_cache_line() {
+        let filter = BloomFilter::with_num_bits(1 << 14).blocked_hashes(4);
+        assert_eq!(filter.block_bits(), recommend_block_bits(64));
+        assert_eq!(recommend_block_bits(64), 512);
+        assert_eq!(recommend_block_bits(128), 1024);
+        assert_eq!(recommend_block_bits(48), 256);
+        assert_eq!(recommend_block_bits(0), 64);
+    }
+
+    #[test]
+    fn empty_block_fast_path() {
+        let mut filter = BloomFilter::with_num_bits(1 << 16)
+            .seed(&1)
+            .blocked_hashes(6);
+        filter.extend(0..20u64);
+        let mut rng = fastrand::Rng::with_seed(7);
+        let mut empty_blocks = 0;
+        for _ in 0..100_000 {
+            let hash = rng.u64(..);
+            let block = filter.block(hash);
+            empty_blocks += filter.block_words(block).iter().all(|&word| word == 0) as usize;
+            assert_eq!(filter.contains_hash(hash), filter.probe(block, hash));
+        }
+        assert!(empty_blocks > 50_000);
+        assert!((0..20u64).all(|x| filter.contains(&x)));
+    }
+
+    #[test]
+    fn with_num_blocks() {
+        let filter = BlockedBloomFilter::with_num_blocks(16).blocked_hashes(6);
+        assert_eq!(filter.block_bits(), 512);
+        assert_eq!(filter.num_bits(), 8192);
+        assert_eq!(filter.num_blocks(), 16);
+    }
+
+    #[test]
+    fn partial_block_unused() {
+        let filter = BloomFilter::with_num_bits(4200).blocked_hashes(4);
+        assert_eq!(filter.num_blocks(), 8);
+        assert_eq!(filter.num_bits(), 4224);
+    }
+
+    #[test]
+    fn few_blocks_unblocked() {
+        for num_bits in [64, 512, 4032] {
+            let mut filter = BloomFilter::with_num_bits(num_bits)
+                .seed(&1)
+                .blocked_hashes(4);
+            let mut bloom = BloomFilter::with_num_bits(num_bits).seed(&1).hashes(4);
+            assert!(!filter.is_blocked());
+            for x in 0..50u64 {
+                assert_eq!(filter.insert(&x), bloom.insert(&x));
+            }
+            assert_eq!(filter.as_slice(), bloom.as_slice());
+            assert_eq!(filter.expected_false_pos(50), bloom.expected_false_pos(50));
+        }
+        assert!(BloomFilter::with_num_bits(4096)
+            .blocked_hashes(4)
+            .is_blocked());
+    }
+
+    #[test]
+    fn tiny_filter_fp_matches_theory() {
+        let num_bits = 128;
+        let num_items = 10;
+        let trials = 200;
+        let queries = 10_000;
+        let mut rng = fastrand::Rng::with_seed(42);
+        let mut false_positives = 0;
+        let mut num_hashes = 0;
+        for seed in 0..trials {
+            let mut filter = BloomFilter::with_num_bits(num_bits)
+                .seed(&seed)
+                .blocked_hashes(optimal_hashes(num_bits, num_items));
+            num_hashes = filter.num_hashes();
+            for _ in 0..num_items {
+                filter.insert_hash(rng.u64(..));
+            }
+            false_positives += (0..queries)
+                .filter(|_| filter.contains_hash(rng.u64(..)))
+                .count();
+        }
+        let sample_fp = false_positives as f64 / (trials as usize * queries) as f64;
+        let density = expected_density(num_hashes, num_bits, num_items);
+        let fp = expected_false_pos(num_hashes, density);
+        let err = (sample_fp - fp).abs() / fp;
+        assert!(err < 0.25, "fp: {fp}, sample fp: {sample_fp}");
+    }
+
+    #[test]
+    fn sized_for_fp() {
+        for (fp, num_items) in [
+            (0.1, 100),
+            (0.01, 1000),
+            (0.001, 10_000),
+            (0.01, 0),
+            (0.01, 10),
+        ] {
+            let mut filter = BloomFilter::with_false_pos(fp)
+                .seed(&1)
+                .blocked_expected_items(num_items);
+            let standard = BloomFilter::with_false_pos(fp).expected_items(num_items);
+            assert!(filter.num_bits() >= standard.num_bits());
+            assert!(!filter.is_blocked() || filter.num_bits() % BLOCK_BITS == 0);
+            assert!(filter.expected_false_pos(num_items) <= fp);
+
+            filter.extend(0..num_items as u64);
+            let trials = 100_000;
+            let false_positives = (num_items as u64..(num_items + trials) as u64)
+                .filter(|x| filter.contains(x))
+                .count();
+            let sample_fp = false_positives as f64 / trials as f64;
+            assert!(sample_fp < fp * 1.2, "sample fp: {sample_fp}, target: {fp}");
+        }
+    }
+}
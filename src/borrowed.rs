@@ -0,0 +1,172 @@
+use crate::{
+    bit_vector::coord, index, BuildError, BuildHasher, DefaultHasher, DoubleHasher, FromBytesError,
+    MAX_NUM_BITS,
+};
+use core::hash::{Hash, Hasher};
+
+/// A read-only Bloom filter that borrows its bits, e.g. from a memory-mapped file, instead of owning them.
+///
+/// [`BloomFilterRef::from_bytes`] reads the bytes written by [`BloomFilter::as_bytes`](crate::BloomFilter::as_bytes)
+/// in place, so loading takes constant time regardless of the Bloom filter's size. Given the same number of hashes and
+/// hasher, a [`BloomFilterRef`] answers [`contains`](Self::contains) exactly like the [`BloomFilter`](crate::BloomFilter)
+/// the bytes came from.
+///
+/// # Examples
+/// ```
+/// use fastbloom::{BloomFilter, BloomFilterRef, DefaultHasher};
+///
+/// let hasher = DefaultHasher::seeded(&[1; 16]);
+/// let bloom = BloomFilter::with_num_bits(1024).hasher(hasher.clone()).items([1, 2].iter());
+/// let borrowed = BloomFilterRef::from_bytes(bloom.as_bytes(), bloom.num_hashes(), hasher).unwrap();
+/// assert!(borrowed.contains(&1));
+/// assert!(!borrowed.contains(&3));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BloomFilterRef<'a, S = DefaultHasher> {
+    bits: &'a [u64],
+    num_hashes: u32,
+    hasher: S,
+}
+
+impl<'a, S: BuildHasher> BloomFilterRef<'a, S> {
+    /// Borrows `bytes` as the bits of a Bloom filter with `num_hashes` hashes per item and `hasher`, without copying them.
+    ///
+    /// The bytes are read as native-endian `u64` words, as written by [`BloomFilter::as_bytes`](crate::BloomFilter::as_bytes),
+    /// so they must come from a machine of the same endianness.
+    ///
+    /// # Errors
+    /// Returns [`FromBytesError::InvalidLength`] if the number of bytes is not a multiple of 8,
+    /// [`FromBytesError::Misaligned`] if `bytes` does not start at an 8-byte aligned address,
+    /// and [`FromBytesError::InvalidFilter`] if `bytes` is empty, too large, or `num_hashes` is 0.
+    pub fn from_bytes(bytes: &'a [u8], num_hashes: u32, hasher: S) -> Result<Self, FromBytesError> {
+        if num_hashes == 0 {
+            return Err(FromBytesError::InvalidFilter(BuildError::ZeroHashes));
+        }
+        if bytes.is_empty() {
+            return Err(FromBytesError::InvalidFilter(BuildError::EmptyBitVec));
+        }
+        if bytes.len() % 8 != 0 {
+            return Err(FromBytesError::InvalidLength);
+        }
+        if bytes.as_ptr() as usize % core::mem::align_of::<u64>() != 0 {
+            return Err(FromBytesError::Misaligned);
+        }
+        let num_words = bytes.len() / 8;
+        if num_words > MAX_NUM_BITS / 64 {
+            return Err(FromBytesError::InvalidFilter(BuildError::TooManyBits));
+        }
+        // SAFETY: `bytes` is aligned for `u64` and its length is a multiple of 8, checked above,
+        // `u64` has no invalid bit patterns, and the words cover exactly the memory of `bytes` for the same lifetime.
+        let bits = unsafe { core::slice::from_raw_parts(bytes.as_ptr().cast::<u64>(), num_words) };
+        Ok(Self {
+            bits,
+            num_hashes,
+            hasher,
+        })
+    }
+
+    /// Checks if an element is possibly in the Bloom filter.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
+    #[inline]
+    pub fn contains(&self, val: &(impl Hash + ?Sized)) -> bool {
+        self.contains_hash(self.source_hash(val))
+    }
+
+    /// Checks if the hash of an element is possibly in the Bloom filter.
+    /// That is the element is pre-hashed and all subsequent hashes are derived from this "source" hash.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
+    #[inline]
+    pub fn contains_hash(&self, hash: u64) -> bool {
+        let num_bits = self.num_bits();
+        let mut hasher = DoubleHasher::new(hash);
+        self.check(index(num_bits, hash))
+            && (1..self.num_hashes).all(|_| self.check(index(num_bits, hasher.next())))
+    }
+
+    #[inline]
+    fn check(&self, i: usize) -> bool {
+        let (word, bit) = coord(i);
+        self.bits[word] & bit > 0
+    }
+
+    /// Returns the number of hashes per item.
+    #[inline]
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    /// Returns the total number of bits of the Bloom filter.
+    #[inline]
+    pub fn num_bits(&self) -> usize {
+        self.bits.len() * 64
+    }
+
+    /// Returns the borrowed slice of this Bloom filter's bit contents.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u64] {
+        self.bits
+    }
+
+    /// Returns the hash of `val` using this Bloom filter's hasher.
+    /// The resulting value can be used in [`Self::contains_hash`].
+    #[inline]
+    pub fn source_hash(&self, val: &(impl Hash + ?Sized)) -> u64 {
+        let mut state = self.hasher.build_hasher();
+        val.hash(&mut state);
+        state.finish()
+    }
+}
+
+#[cfg(not(feature = "loom"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BloomFilter;
+
+    #[test]
+    fn matches_owned() {
+        for (num_bits, num_hashes) in [(64, 1), (1024, 4), (1 << 16, 7)] {
+            let hasher = DefaultHasher::seeded(&[1; 16]);
+            let mut bloom = BloomFilter::with_num_bits(num_bits)
+                .hasher(hasher.clone())
+                .hashes(num_hashes);
+            bloom.extend(0..num_bits as u64 / 16);
+            let borrowed =
+                BloomFilterRef::from_bytes(bloom.as_bytes(), num_hashes, hasher).unwrap();
+            assert_eq!(borrowed.num_bits(), bloom.num_bits());
+            assert_eq!(borrowed.as_slice(), bloom.as_slice());
+            for x in 0..10_000u64 {
+                assert_eq!(borrowed.contains(&x), bloom.contains(&x));
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_bytes() {
+        let bloom = BloomFilter::with_num_bits(1024).hashes(4);
+        let bytes = bloom.as_bytes();
+        let from_bytes = |bytes, num_hashes| {
+            BloomFilterRef::from_bytes(bytes, num_hashes, DefaultHasher::default()).map(|_| ())
+        };
+        assert_eq!(from_bytes(bytes, 4), Ok(()));
+        assert_eq!(
+            from_bytes(&bytes[..12], 4),
+            Err(FromBytesError::InvalidLength)
+        );
+        assert_eq!(from_bytes(&bytes[1..9], 4), Err(FromBytesError::Misaligned));
+        assert_eq!(
+            from_bytes(&[], 4),
+            Err(FromBytesError::InvalidFilter(BuildError::EmptyBitVec))
+        );
+        assert_eq!(
+            from_bytes(bytes, 0),
+            Err(FromBytesError::InvalidFilter(BuildError::ZeroHashes))
+        );
+    }
+}
@@ -1,6 +1,128 @@
 use crate::{math::*, AtomicBloomFilter, BloomFilter, BuildHasher, DefaultHasher};
-use alloc::vec::Vec;
-use core::{cmp::max, f64::consts::LN_2, hash::Hash};
+use alloc::{vec, vec::Vec};
+use core::{cmp::max, fmt, hash::Hash};
+
+/// An error returned when a Bloom filter cannot be built from the requested parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BuildError {
+    /// The number of expected items was 0.
+    ZeroItems,
+    /// The number of bits required exceeds `isize::MAX`, or the number of bits of a bit vector does not fit in a `usize`.
+    TooManyBits,
+    /// The bit vector was empty.
+    EmptyBitVec,
+    /// The number of hashes was 0.
+    ZeroHashes,
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::ZeroItems => f.write_str("expected items must be greater than 0"),
+            BuildError::TooManyBits => f.write_str("required number of bits is too large"),
+            BuildError::EmptyBitVec => f.write_str("bit vector must not be empty"),
+            BuildError::ZeroHashes => f.write_str("number of hashes must be greater than 0"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BuildError {}
+
+/// The bits of a bits builder.
+///
+/// Builders created from a number of bits only allocate it once a Bloom filter is built, so builder methods that construct
+/// other filters, e.g. [`counting_hashes`](BuilderWithBits::counting_hashes), don't allocate bits only to discard them.
+#[derive(Debug, Clone)]
+pub(crate) enum BuilderData {
+    /// The number of words of an all-zero bit vector.
+    Zeros(usize),
+    Bits(Vec<u64>),
+}
+
+impl BuilderData {
+    fn num_words(&self) -> usize {
+        match self {
+            BuilderData::Zeros(num_words) => *num_words,
+            BuilderData::Bits(bits) => bits.len(),
+        }
+    }
+
+    fn into_vec(self) -> Vec<u64> {
+        match self {
+            BuilderData::Zeros(num_words) => vec![0; num_words],
+            BuilderData::Bits(bits) => bits,
+        }
+    }
+}
+
+impl PartialEq for BuilderData {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (BuilderData::Zeros(a), BuilderData::Zeros(b)) => a == b,
+            (BuilderData::Bits(a), BuilderData::Bits(b)) => a == b,
+            (BuilderData::Zeros(num_words), BuilderData::Bits(bits))
+            | (BuilderData::Bits(bits), BuilderData::Zeros(num_words)) => {
+                *num_words == bits.len() && bits.iter().all(|&x| x == 0)
+            }
+        }
+    }
+}
+
+/// (De)serializes a bits builder's data as its number of bits if all bits are zero, and as the bits otherwise.
+#[cfg(feature = "serde")]
+mod serde_data {
+    use super::BuilderData;
+    use alloc::vec::Vec;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    #[serde(untagged)]
+    enum DataRef<'a> {
+        NumBits(usize),
+        Bits(&'a [u64]),
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Data {
+        NumBits(usize),
+        Bits(Vec<u64>),
+    }
+
+    pub(crate) fn serialize<S: Serializer>(data: &BuilderData, s: S) -> Result<S::Ok, S::Error> {
+        match data {
+            BuilderData::Bits(bits) if bits.iter().any(|&x| x != 0) => DataRef::Bits(bits),
+            _ => DataRef::NumBits(data.num_words() * 64),
+        }
+        .serialize(s)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<BuilderData, D::Error> {
+        let data = match Data::deserialize(d)? {
+            Data::NumBits(num_bits) if num_bits <= crate::MAX_NUM_BITS => {
+                BuilderData::Zeros((num_bits + 63) / 64)
+            }
+            Data::NumBits(_) => return Err(D::Error::custom("too many bits")),
+            Data::Bits(bits) => BuilderData::Bits(bits),
+        };
+        match data.num_words() == 0 {
+            true => Err(D::Error::custom("bits must not be empty")),
+            false => Ok(data),
+        }
+    }
+}
+
+/// Deserializes a false positive rate, rejecting rates that can't be built.
+#[cfg(feature = "serde")]
+fn deserialize_fp<'de, D: serde::Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    use serde::{de::Error, Deserialize};
+    match f64::deserialize(d)? {
+        fp if fp > 0.0 => Ok(fp),
+        _ => Err(D::Error::custom("false_pos must be greater than 0")),
+    }
+}
 
 macro_rules! builder_with_bits {
     ($name:ident, $($m:ident)?, $bloom:ident) => {
@@ -15,9 +137,15 @@ macro_rules! builder_with_bits {
         #[doc = concat!("let builder = ", stringify!($bloom), "::with_num_bits(1024);")]
         #[doc = concat!("let builder = ", stringify!($bloom), "::from_vec(vec![0; 8]);")]
         /// ```
+        ///
+        /// With the `serde` feature, builders can be (de)serialized to store a Bloom filter's design separately from its items.
+        /// A builder whose bits are all zeros, e.g. from `with_num_bits`, is serialized as just its number of bits;
+        /// otherwise the bits themselves are serialized. This relies on a self-describing format such as JSON or YAML.
         #[derive(Debug, Clone)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub struct $name<S = DefaultHasher> {
-            pub(crate) data: Vec<u64>,
+            #[cfg_attr(feature = "serde", serde(rename = "bits", with = "serde_data"))]
+            pub(crate) data: BuilderData,
             pub(crate) hasher: S,
         }
 
@@ -32,6 +160,9 @@ macro_rules! builder_with_bits {
             /// Sets the seed for this builder. The later constructed Bloom filter
             /// will use this seed when hashing items.
             ///
+            /// The seed's big-endian bytes are used as the hasher's key, so the same seed
+            /// produces the same Bloom filter on every platform.
+            ///
             /// # Examples
             ///
             /// ```
@@ -43,6 +174,23 @@ macro_rules! builder_with_bits {
                 self.hasher = DefaultHasher::seeded(&seed.to_be_bytes());
                 self
             }
+
+            /// Sets the seed for this builder from seed material of any length, e.g. a 32-byte key.
+            ///
+            /// The bytes are hashed with 128-bit SipHash-1-3 under a fixed key, and the result is used as the hasher's key,
+            /// so the same bytes produce the same Bloom filter on every platform.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($bloom), ";")]
+            ///
+            #[doc = concat!("let bloom = ", stringify!($bloom), "::with_num_bits(1024).seed_from_bytes(&[7; 32]).hashes(4);")]
+            /// ```
+            pub fn seed_from_bytes(mut self, bytes: &[u8]) -> Self {
+                self.hasher = DefaultHasher::seeded_from_bytes(bytes);
+                self
+            }
         }
 
         impl<S: BuildHasher> $name<S> {
@@ -79,18 +227,43 @@ macro_rules! builder_with_bits {
             /// ```
             pub fn hashes(self, num_hashes: u32) -> $bloom<S> {
                 $bloom {
-                    bits: self.data.into_iter().collect(),
+                    bits: self.data.into_vec().into_iter().collect(),
                     num_hashes_minus_one: max(1, num_hashes) - 1,
                     hasher: self.hasher,
+                    hash_calls: Default::default(),
                 }
             }
 
+            /// Fallible version of [`hashes`](Self::hashes).
+            ///
+            /// # Errors
+            /// Returns [`BuildError::ZeroHashes`] if `num_hashes` is 0 instead of treating it as 1,
+            /// e.g. to validate a number of hashes from a config file.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::{", stringify!($bloom), ", BuildError};")]
+            ///
+            #[doc = concat!("let bloom = ", stringify!($bloom), "::with_num_bits(1024).try_hashes(4);")]
+            /// assert_eq!(bloom.unwrap().num_hashes(), 4);
+            #[doc = concat!("let bloom = ", stringify!($bloom), "::with_num_bits(1024).try_hashes(0);")]
+            /// assert_eq!(bloom.unwrap_err(), BuildError::ZeroHashes);
+            /// ```
+            pub fn try_hashes(self, num_hashes: u32) -> Result<$bloom<S>, BuildError> {
+                if num_hashes == 0 {
+                    return Err(BuildError::ZeroHashes);
+                }
+                Ok(self.hashes(num_hashes))
+            }
+
             /// "Consumes" this builder, using the provided `expected_items` to return an
             #[doc = concat!("empty [`", stringify!($bloom), "`]. The number of hashes is optimized based on `expected_items`")]
             #[doc = concat!("to maximize Bloom filter accuracy (minimize false positives chance on [`", stringify!($bloom), "::contains`]).")]
             /// More or less than `expected_items` may be inserted into Bloom filter.
             ///
             /// Note: `expected_items` will internally be set to 1 if 0 is specified.
+            /// The number of hashes is capped at 64: beyond that, extra hashes only make
+            /// inserts and lookups slower for a negligible reduction in false positives.
             ///
             /// # Examples
             ///
@@ -100,11 +273,20 @@ macro_rules! builder_with_bits {
             #[doc = concat!("let bloom = ", stringify!($bloom), "::with_num_bits(1024).expected_items(500);")]
             /// ```
             pub fn expected_items(self, expected_items: usize) -> $bloom<S> {
-                let expected_items = max(1, expected_items);
-                let hashes = optimal_hashes(self.data.len() * 64, expected_items);
+                let hashes = self.optimal_hashes(expected_items);
                 self.hashes(hashes)
             }
 
+            /// Returns the number of bits of the Bloom filter this builder constructs.
+            pub(crate) fn num_bits(&self) -> usize {
+                self.data.num_words() * 64
+            }
+
+            /// Returns the number of hashes [`expected_items`](Self::expected_items) chooses for `expected_items`.
+            pub(crate) fn optimal_hashes(&self, expected_items: usize) -> u32 {
+                optimal_hashes_capped(self.num_bits(), max(1, expected_items), DEFAULT_MAX_HASHES)
+            }
+
             #[doc = concat!("\"Consumes\" this builder and constructs a [`", stringify!($bloom), "`] containing")]
             /// all values in `items`. The number of hashes per item
             /// is optimized based on `items.len()` to maximize Bloom filter accuracy
@@ -134,7 +316,7 @@ builder_with_bits!(BuilderWithBits, mut, BloomFilter);
 builder_with_bits!(AtomicBuilderWithBits, , AtomicBloomFilter);
 
 macro_rules! builder_with_fp {
-    ($name:ident, $($m:ident)?, $bloom:ident) => {
+    ($name:ident, $($m:ident)?, $bloom:ident, $bits:ident) => {
         /// A Bloom filter builder with an immutable false positive rate.
         ///
         /// This type can be used to construct an instance of [`BloomFilter`] via the builder pattern.
@@ -146,8 +328,12 @@ macro_rules! builder_with_fp {
         ///
         #[doc = concat!("let builder = ", stringify!($bloom), "::with_false_pos(0.01);")]
         /// ```
+        ///
+        /// With the `serde` feature, builders can be (de)serialized to store a Bloom filter's design separately from its items.
         #[derive(Debug, Clone)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub struct $name<S = DefaultHasher> {
+            #[cfg_attr(feature = "serde", serde(rename = "false_pos", deserialize_with = "deserialize_fp"))]
             pub(crate) desired_fp_rate: f64,
             pub(crate) hasher: S,
         }
@@ -163,6 +349,9 @@ macro_rules! builder_with_fp {
             /// Sets the seed for this builder. The later constructed Bloom filter
             /// will use this seed when hashing items.
             ///
+            /// The seed's big-endian bytes are used as the hasher's key, so the same seed
+            /// produces the same Bloom filter on every platform.
+            ///
             /// # Examples
             ///
             /// ```
@@ -174,6 +363,23 @@ macro_rules! builder_with_fp {
                 self.hasher = DefaultHasher::seeded(&seed.to_be_bytes());
                 self
             }
+
+            /// Sets the seed for this builder from seed material of any length, e.g. a 32-byte key.
+            ///
+            /// The bytes are hashed with 128-bit SipHash-1-3 under a fixed key, and the result is used as the hasher's key,
+            /// so the same bytes produce the same Bloom filter on every platform.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($bloom), ";")]
+            ///
+            #[doc = concat!("let bloom = ", stringify!($bloom), "::with_false_pos(0.001).seed_from_bytes(&[7; 32]).expected_items(100);")]
+            /// ```
+            pub fn seed_from_bytes(mut self, bytes: &[u8]) -> Self {
+                self.hasher = DefaultHasher::seeded_from_bytes(bytes);
+                self
+            }
         }
 
         impl<S: BuildHasher> $name<S> {
@@ -210,13 +416,43 @@ macro_rules! builder_with_fp {
             #[doc = concat!("let bloom = ", stringify!($bloom), "::with_false_pos(0.001).expected_items(500);")]
             /// ```
             pub fn expected_items(self, expected_items: usize) -> $bloom<S> {
-                let expected_items = max(1, expected_items);
-                let num_bits = optimal_size(expected_items, self.desired_fp_rate);
-                $bloom::new_builder(num_bits)
-                    .hasher(self.hasher)
+                self.into_bits_builder(expected_items)
                     .expected_items(expected_items)
             }
 
+            /// Returns a builder with the number of bits [`expected_items`](Self::expected_items) chooses for `expected_items`.
+            pub(crate) fn into_bits_builder(self, expected_items: usize) -> $bits<S> {
+                let num_bits = optimal_size(max(1, expected_items), self.desired_fp_rate);
+                $bloom::new_builder(num_bits).hasher(self.hasher)
+            }
+
+            /// Fallible version of [`expected_items`](Self::expected_items).
+            ///
+            /// # Errors
+            /// Returns [`BuildError::ZeroItems`] if `expected_items` is 0 instead of treating it as 1,
+            /// and [`BuildError::TooManyBits`] if the number of bits needed to reach the target
+            /// false positive rate exceeds `isize::MAX`.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            #[doc = concat!("use fastbloom::{", stringify!($bloom), ", BuildError};")]
+            ///
+            #[doc = concat!("let bloom = ", stringify!($bloom), "::with_false_pos(0.001).try_expected_items(500);")]
+            /// assert!(bloom.is_ok());
+            #[doc = concat!("let bloom = ", stringify!($bloom), "::with_false_pos(0.001).try_expected_items(0);")]
+            /// assert_eq!(bloom.unwrap_err(), BuildError::ZeroItems);
+            /// ```
+            pub fn try_expected_items(self, expected_items: usize) -> Result<$bloom<S>, BuildError> {
+                if expected_items == 0 {
+                    return Err(BuildError::ZeroItems);
+                }
+                if raw_optimal_size(expected_items, self.desired_fp_rate) > isize::MAX as f64 {
+                    return Err(BuildError::TooManyBits);
+                }
+                Ok(self.expected_items(expected_items))
+            }
+
             #[doc = concat!("\"Consumes\" this builder and constructs a [`", stringify!($bloom), "`] containing")]
             /// all values in `items`. The number of hashes per item and underlying memory
             /// is optimized based on `items.len()` to meet the desired false positive rate.
@@ -241,38 +477,37 @@ macro_rules! builder_with_fp {
     };
 }
 
-builder_with_fp!(BuilderWithFalsePositiveRate, mut, BloomFilter);
-builder_with_fp!(AtomicBuilderWithFalsePositiveRate, , AtomicBloomFilter);
-
-/// Returns the optimal (for false positive rate) number of hashes to perform for an item given the expected number of items in the bloom filter.
-pub fn optimal_hashes(num_bits: usize, num_items: usize) -> u32 {
-    // Proof: <https://gopiandcode.uk/logs/log-bloomfilters-debunked.html>.
-    let num_bits = num_bits as f64;
-    let hashes = LN_2 * num_bits / num_items as f64;
-    max(round(hashes) as u32, 1)
-}
-
-/// Returns the smallest size in bits of a Bloom filter containing `num_items` items to achieve the target false positive rate.
-pub fn optimal_size(num_items: usize, fp: f64) -> usize {
-    let num_items = num_items as f64;
-    let log2_2 = LN_2 * LN_2;
-    let result = 8 * ceil(num_items * ln(fp) / (-8.0 * log2_2)) as usize;
-    max(result, 64)
-}
-
-/// Returns the probability of a "1" bit in the Bloom filter.
-pub fn expected_density(hashes: u32, bits: usize, items: usize) -> f64 {
-    let total_sets = (items * hashes as usize) as f64;
-    let bits = bits as f64;
-    let prob_set = 1.0 / bits;
-    let prob_not_set = 1.0 - prob_set;
-    let prob_all_not_set = crate::math::pow(prob_not_set, total_sets);
-    1.0 - prob_all_not_set
-}
-
-/// Returns the expected false positive rate of a Bloom filter.
-pub fn expected_false_pos(hashes: u32, density: f64) -> f64 {
-    crate::math::pow(density, hashes as f64)
+builder_with_fp!(
+    BuilderWithFalsePositiveRate,
+    mut,
+    BloomFilter,
+    BuilderWithBits
+);
+builder_with_fp!(AtomicBuilderWithFalsePositiveRate, , AtomicBloomFilter, AtomicBuilderWithBits);
+
+/// Returns a [`BloomFilter`] containing all values in `items`, sized to meet the target false positive rate `fp`.
+///
+/// This is shorthand for `BloomFilter::with_false_pos(fp).expected_items(len)` followed by inserting every item,
+/// where `len` is the number of items.
+///
+/// # Panics
+/// Panics if the false positive rate, `fp`, is 0.
+///
+/// # Examples
+/// ```
+/// let bloom = fastbloom::from_items(vec!["42", "🦀"], 0.001);
+/// assert!(bloom.contains("42"));
+/// assert!(bloom.contains("🦀"));
+/// ```
+pub fn from_items<T: Hash, I>(items: I, fp: f64) -> BloomFilter
+where
+    I: IntoIterator<Item = T>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let items = items.into_iter();
+    let mut filter = BloomFilter::with_false_pos(fp).expected_items(items.len());
+    filter.extend(items);
+    filter
 }
 
 #[cfg(test)]
@@ -304,6 +539,225 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_from_items() {
+        let items: Vec<u64> = (0..10_000).collect();
+        let bloom = from_items(&items, 0.01);
+        assert_eq!(
+            bloom.num_bits(),
+            (optimal_size(10_000, 0.01) + 63) / 64 * 64
+        );
+        assert!(items.iter().all(|x| bloom.contains(x)));
+        let num_false_pos = (10_000..110_000u64).filter(|x| bloom.contains(x)).count();
+        assert!((num_false_pos as f64 / 100_000.0) < 0.015);
+    }
+
+    #[test]
+    fn test_seed_from_bytes() {
+        let items: Vec<u64> = (0..1000).collect();
+        let key = [42u8; 32];
+        let a = BloomFilter::with_num_bits(1 << 14)
+            .seed_from_bytes(&key)
+            .items(items.iter());
+        let b = BloomFilter::with_false_pos(0.01)
+            .seed_from_bytes(&key)
+            .expected_items(1000);
+        assert_eq!(a.source_hash(&1), b.source_hash(&1));
+        assert_eq!(
+            a,
+            BloomFilter::with_num_bits(1 << 14)
+                .seed_from_bytes(&key)
+                .items(items.iter())
+        );
+
+        let mut other_key = key;
+        other_key[31] = 0;
+        let c = BloomFilter::with_num_bits(1 << 14)
+            .seed_from_bytes(&other_key)
+            .items(items.iter());
+        assert_ne!(a.as_slice(), c.as_slice());
+        let d = BloomFilter::with_num_bits(1 << 14)
+            .seed_from_bytes(&key[..16])
+            .items(items.iter());
+        assert_ne!(a.as_slice(), d.as_slice());
+    }
+
+    #[test]
+    fn test_try_hashes() {
+        let bloom = BloomFilter::with_num_bits(1024).try_hashes(7).unwrap();
+        assert_eq!(bloom.num_hashes(), 7);
+        assert_eq!(bloom.num_bits(), 1024);
+        let bloom = AtomicBloomFilter::with_num_bits(1024)
+            .try_hashes(1)
+            .unwrap();
+        assert_eq!(bloom.num_hashes(), 1);
+
+        let err = BloomFilter::with_num_bits(1024).try_hashes(0).unwrap_err();
+        assert_eq!(err, BuildError::ZeroHashes);
+        assert!(AtomicBloomFilter::with_num_bits(1024)
+            .try_hashes(0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_plan() {
+        for num_items in [0, 1, 100, 1000, 12_345, 1_000_000] {
+            for fp in [0.5, 0.1, 0.01, 0.0001, 1e-10] {
+                let params = plan(num_items, fp);
+                let bloom = BloomFilter::with_num_bits(params.num_bits).hashes(params.num_hashes);
+                assert_eq!(bloom.num_bits(), params.num_bits);
+                assert_eq!(bloom.num_bytes(), params.num_bytes);
+                assert_eq!(bloom.num_hashes(), params.num_hashes);
+
+                let built = BloomFilter::with_false_pos(fp).expected_items(num_items);
+                assert_eq!(built.num_bits(), params.num_bits);
+                assert_eq!(built.num_hashes(), params.num_hashes);
+            }
+        }
+    }
+
+    #[test]
+    fn test_optimal_size_bits() {
+        for items_mag in 0..=24 {
+            let items = 2usize.pow(items_mag);
+            for fp_mag in 1..=16 {
+                let fp = 1.0f64 / 10u64.pow(fp_mag) as f64;
+                let bits = optimal_size_bits(items, fp);
+                assert_eq!(optimal_size(items, fp), max((bits + 7) / 8 * 8, 64));
+            }
+        }
+    }
+
+    #[test]
+    fn test_math_near_u32_max() {
+        let mut prev_size = 0;
+        for num_items in (u32::MAX as usize - 4)..=(u32::MAX as usize) {
+            let size = optimal_size(num_items, 0.001);
+            assert!(size >= prev_size);
+            prev_size = size;
+
+            let num_bits = size.min(crate::MAX_NUM_BITS);
+            let hashes = optimal_hashes(num_bits, num_items);
+            assert!((1..=64).contains(&hashes));
+            let density = expected_density(hashes, num_bits, num_items);
+            assert!((0.0..=1.0).contains(&density));
+            let fp = expected_false_pos(hashes, density);
+            assert!((0.0..=1.0).contains(&fp));
+        }
+
+        assert_eq!(optimal_size(usize::MAX, 1e-9), usize::MAX);
+        assert_eq!(optimal_size_bits(usize::MAX, 1e-9), usize::MAX);
+        let density = expected_density(u32::MAX, 64, usize::MAX);
+        assert_eq!(density, 1.0);
+    }
+
+    #[test]
+    fn test_achievable_fp() {
+        for bits_mag in 10..=20 {
+            let bits = 2usize.pow(bits_mag);
+            for items_mag in 4..=12 {
+                let items = 2usize.pow(items_mag);
+                let filter = crate::BloomFilter::with_num_bits(bits).expected_items(items);
+                assert_eq!(achievable_fp(bits, items), filter.expected_false_pos(items));
+                for h in [filter.num_hashes() - 1, filter.num_hashes() + 1] {
+                    if h > DEFAULT_MAX_HASHES {
+                        continue;
+                    }
+                    let density = expected_density(h, bits, items);
+                    assert!(achievable_fp(bits, items) <= expected_false_pos(h, density));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_atomic_seed_reproducible() {
+        use crate::{AtomicBloomFilter, BloomFilter};
+
+        let items: Vec<u64> = (0..1000).collect();
+        for seed in [0u128, 1, 42, u128::MAX] {
+            let a = AtomicBloomFilter::with_num_bits(4096)
+                .seed(&seed)
+                .items(items.iter());
+            let b = AtomicBloomFilter::with_num_bits(4096)
+                .seed(&seed)
+                .items(items.iter());
+            assert_eq!(a.iter().collect::<Vec<_>>(), b.iter().collect::<Vec<_>>());
+            let non = BloomFilter::with_num_bits(4096)
+                .seed(&seed)
+                .items(items.iter());
+            assert_eq!(a.iter().collect::<Vec<_>>(), non.iter().collect::<Vec<_>>());
+
+            let a = AtomicBloomFilter::with_false_pos(0.01)
+                .seed(&seed)
+                .items(items.iter());
+            let b = AtomicBloomFilter::with_false_pos(0.01)
+                .seed(&seed)
+                .items(items.iter());
+            assert_eq!(a.iter().collect::<Vec<_>>(), b.iter().collect::<Vec<_>>());
+            let non = BloomFilter::with_false_pos(0.01)
+                .seed(&seed)
+                .items(items.iter());
+            assert_eq!(a.iter().collect::<Vec<_>>(), non.iter().collect::<Vec<_>>());
+        }
+
+        let a = AtomicBloomFilter::with_num_bits(4096)
+            .seed(&1)
+            .items(items.iter());
+        let b = AtomicBloomFilter::with_num_bits(4096)
+            .seed(&2)
+            .items(items.iter());
+        assert_ne!(a.iter().collect::<Vec<_>>(), b.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_builder_data_lazy() {
+        use crate::BloomFilter;
+        use alloc::vec;
+
+        let builder = BloomFilter::with_num_bits(1000);
+        assert!(matches!(builder.data, BuilderData::Zeros(16)));
+        assert_eq!(builder, BloomFilter::from_vec(vec![0; 16]));
+        assert_ne!(builder, BloomFilter::from_vec(vec![0; 15]));
+        assert_ne!(builder, BloomFilter::from_vec(vec![1; 16]));
+        assert_eq!(builder.hashes(4).as_slice(), &[0; 16]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_builders() {
+        use crate::{AtomicBloomFilter, BloomFilter};
+        use alloc::vec;
+
+        let builder = BloomFilter::with_num_bits(1024).seed(&1);
+        let json = serde_json::to_value(&builder).unwrap();
+        assert_eq!(json["bits"], 1024);
+        let new: BuilderWithBits = serde_json::from_value(json).unwrap();
+        let (new, orig) = (new.hashes(4), builder.hashes(4));
+        assert_eq!(new, orig);
+        assert_eq!(new.source_hash(&1), orig.source_hash(&1));
+
+        let builder = AtomicBloomFilter::from_vec(vec![1, 0, 3]).seed(&1);
+        let json = serde_json::to_value(&builder).unwrap();
+        assert_eq!(json["bits"], serde_json::json!([1, 0, 3]));
+        let new: AtomicBuilderWithBits = serde_json::from_value(json).unwrap();
+        assert_eq!(new.hashes(4), builder.hashes(4));
+
+        let builder = AtomicBloomFilter::with_false_pos(0.01).seed(&1);
+        let json = serde_json::to_value(&builder).unwrap();
+        assert_eq!(json["false_pos"], 0.01);
+        let new: AtomicBuilderWithFalsePositiveRate = serde_json::from_value(json).unwrap();
+        let items: Vec<u64> = (0..100).collect();
+        assert_eq!(new.items(items.iter()), builder.items(items.iter()));
+
+        let mut json = serde_json::to_value(BloomFilter::with_false_pos(0.01)).unwrap();
+        json["false_pos"] = 0.0.into();
+        assert!(serde_json::from_value::<BuilderWithFalsePositiveRate>(json).is_err());
+        let mut json = serde_json::to_value(BloomFilter::with_num_bits(64)).unwrap();
+        json["bits"] = 0.into();
+        assert!(serde_json::from_value::<BuilderWithBits>(json).is_err());
+    }
+
     fn density_err(d: f64) -> f64 {
         (0.5 - d).abs()
     }
@@ -361,13 +815,57 @@ mod for_accuracy_tests {
 
 #[cfg(test)]
 mod for_size_tests {
-    use crate::{AtomicBloomFilter, BloomFilter};
+    use crate::{AtomicBloomFilter, BloomFilter, BuildError};
+    use alloc::{vec, vec::Vec};
 
     #[test]
     fn test_size() {
         let _: BloomFilter = BloomFilter::new_with_false_pos(0.0001).expected_items(10000);
     }
 
+    #[test]
+    fn test_try_expected_items() {
+        assert!(BloomFilter::with_false_pos(0.01)
+            .try_expected_items(1000)
+            .is_ok());
+        assert_eq!(
+            BloomFilter::with_false_pos(0.01).try_expected_items(0),
+            Err(BuildError::ZeroItems)
+        );
+        assert_eq!(
+            AtomicBloomFilter::with_false_pos(1e-300).try_expected_items(usize::MAX),
+            Err(BuildError::TooManyBits)
+        );
+    }
+
+    #[test]
+    fn test_try_from_vec() {
+        assert_eq!(
+            BloomFilter::try_from_vec(vec![]).unwrap_err(),
+            BuildError::EmptyBitVec
+        );
+        assert_eq!(
+            AtomicBloomFilter::try_from_vec(vec![]).unwrap_err(),
+            BuildError::EmptyBitVec
+        );
+        let bloom = BloomFilter::try_from_vec(vec![0; 3]).unwrap().hashes(2);
+        assert_eq!(bloom.num_bits(), 3 * 64);
+        let bloom = AtomicBloomFilter::try_from_vec(vec![1; 3])
+            .unwrap()
+            .hashes(2);
+        assert_eq!(bloom.iter().collect::<Vec<_>>(), vec![1; 3]);
+    }
+
+    #[test]
+    fn test_hashes_capped() {
+        let bloom = BloomFilter::with_num_bits(1 << 24).expected_items(1);
+        assert_eq!(bloom.num_hashes(), 64);
+        let bloom = AtomicBloomFilter::with_num_bits(1 << 24).expected_items(1);
+        assert_eq!(bloom.num_hashes(), 64);
+        assert_eq!(crate::optimal_hashes_capped(1 << 24, 1, 1000), 1000);
+        assert_eq!(crate::optimal_hashes_capped(1 << 10, 100, 64), 7);
+    }
+
     #[test]
     fn test_zero_hashes() {
         let bloom = BloomFilter::with_num_bits(512).hashes(0);
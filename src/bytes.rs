@@ -0,0 +1,473 @@
+use crate::{AtomicBloomFilter, BloomFilter, BuildError, DefaultHasher};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// Identifies the byte format written by [`BloomFilter::to_bytes`] and [`AtomicBloomFilter::to_bytes`].
+const MAGIC: [u8; 4] = *b"FBLM";
+/// The current version of the byte format.
+const VERSION: u8 = 1;
+/// Marks the hasher as [`DefaultHasher`], followed by its two 64-bit SipHash keys.
+const DEFAULT_HASHER: u8 = 0;
+/// magic, version, hasher marker, 2 reserved bytes, num_hashes: u32, num_bits: u64, 2 hasher keys: u64.
+const HEADER_LEN: usize = 4 + 1 + 1 + 2 + 4 + 8 + 8 + 8;
+
+/// An error returned when a Bloom filter cannot be read from bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FromBytesError {
+    /// The bytes do not start with the expected magic number.
+    BadMagic,
+    /// The bytes were written by an unknown version of the format.
+    UnsupportedVersion(u8),
+    /// The bytes were written with an unknown hasher.
+    UnsupportedHasher(u8),
+    /// The bytes are shorter than the header, or their number does not match the header.
+    InvalidLength,
+    /// The bytes are not aligned to 8 bytes, so they cannot be borrowed as `u64`s.
+    Misaligned,
+    /// The header describes a Bloom filter that cannot be built.
+    InvalidFilter(BuildError),
+    /// The string passed to `from_url_safe` is not unpadded base64url.
+    InvalidBase64,
+}
+
+impl fmt::Display for FromBytesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FromBytesError::BadMagic => f.write_str("bytes are not a serialized Bloom filter"),
+            FromBytesError::UnsupportedVersion(v) => write!(f, "unsupported format version {}", v),
+            FromBytesError::UnsupportedHasher(h) => write!(f, "unsupported hasher {}", h),
+            FromBytesError::InvalidLength => {
+                f.write_str("number of bytes does not match the header")
+            }
+            FromBytesError::Misaligned => f.write_str("bytes are not aligned to 8 bytes"),
+            FromBytesError::InvalidFilter(e) => write!(f, "invalid Bloom filter: {}", e),
+            FromBytesError::InvalidBase64 => f.write_str("string is not unpadded base64url"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FromBytesError {}
+
+macro_rules! impl_bytes {
+    ($name:ident) => {
+        impl $name {
+            /// Returns a compact binary representation of this Bloom filter, which can be read back with [`from_bytes`](Self::from_bytes).
+            ///
+            /// The format is a 36-byte header (magic number, format version, hasher, number of hashes, number of bits
+            /// and the hasher's keys) followed by the bits as `u64` words. All integers are little-endian,
+            /// so the bytes can be read on any machine regardless of its endianness.
+            /// [`BloomFilter`] and [`AtomicBloomFilter`] use the same format, so either can read the other's bytes.
+            ///
+            /// This copies the whole Bloom filter into one `Vec`, so it is only suitable for smaller Bloom filters.
+            /// For Bloom filters of many gigabytes, stream the same bytes with `write_to` instead.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let bloom = ", stringify!($name), "::with_num_bits(1024).items([1, 2].iter());")]
+            /// let bytes = bloom.to_bytes();
+            /// assert_eq!(bytes.len(), 36 + 1024 / 8);
+            ///
+            #[doc = concat!("let new = ", stringify!($name), "::from_bytes(&bytes).unwrap();")]
+            /// assert_eq!(bloom, new);
+            /// assert!(new.contains(&1));
+            /// ```
+            pub fn to_bytes(&self) -> Vec<u8> {
+                let mut bytes = Vec::with_capacity(self.serialized_size());
+                bytes.extend_from_slice(&self.header());
+                for word in self.iter() {
+                    bytes.extend_from_slice(&word.to_le_bytes());
+                }
+                bytes
+            }
+
+            /// Writes the same bytes as [`to_bytes`](Self::to_bytes) to `writer`, one `u64` word at a time.
+            ///
+            /// Unlike [`to_bytes`](Self::to_bytes), this never holds more than one word in memory,
+            /// so it suits Bloom filters too large to copy into one contiguous buffer.
+            /// Each word is a separate write, so wrap unbuffered writers in a [`BufWriter`](std::io::BufWriter).
+            ///
+            /// # Errors
+            /// Returns any error from `writer`.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let bloom = ", stringify!($name), "::with_num_bits(1024).items([1, 2].iter());")]
+            /// let mut bytes = Vec::new();
+            /// bloom.write_to(&mut bytes).unwrap();
+            /// assert_eq!(bytes, bloom.to_bytes());
+            /// ```
+            #[cfg(feature = "std")]
+            pub fn write_to<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+                writer.write_all(&self.header())?;
+                for word in self.iter() {
+                    writer.write_all(&word.to_le_bytes())?;
+                }
+                Ok(())
+            }
+
+            /// Returns the number of bytes written by [`to_bytes`](Self::to_bytes) and [`write_to`](Self::write_to):
+            /// the 36-byte header plus `num_bits() / 8`.
+            #[inline]
+            pub fn serialized_size(&self) -> usize {
+                HEADER_LEN + self.num_bits() / 8
+            }
+
+            fn header(&self) -> [u8; HEADER_LEN] {
+                let (key0, key1) = self.hasher.keys();
+                let mut header = [0; HEADER_LEN];
+                header[..4].copy_from_slice(&MAGIC);
+                header[4] = VERSION;
+                header[5] = DEFAULT_HASHER;
+                header[8..12].copy_from_slice(&self.num_hashes().to_le_bytes());
+                header[12..20].copy_from_slice(&(self.num_bits() as u64).to_le_bytes());
+                header[20..28].copy_from_slice(&key0.to_le_bytes());
+                header[28..36].copy_from_slice(&key1.to_le_bytes());
+                header
+            }
+
+            /// Reads a Bloom filter written by [`to_bytes`](Self::to_bytes).
+            ///
+            /// # Errors
+            /// Returns a [`FromBytesError`] if `bytes` were not written by [`to_bytes`](Self::to_bytes),
+            /// were written by an unsupported version, or are truncated or otherwise corrupt.
+            pub fn from_bytes(bytes: &[u8]) -> Result<Self, FromBytesError> {
+                let (bit_vec, num_hashes, hasher) = read_bytes(bytes)?;
+                $name::from_raw_parts(bit_vec, num_hashes, hasher).map_err(FromBytesError::InvalidFilter)
+            }
+
+            /// Returns the bytes of [`to_bytes`](Self::to_bytes) as unpadded base64url (RFC 4648, section 5),
+            /// which can be read back with [`from_url_safe`](Self::from_url_safe).
+            ///
+            /// The string only contains `A-Z`, `a-z`, `0-9`, `-` and `_`, so it can be put in URLs, file names or
+            /// QR codes as is. It is a third longer than the bytes, so this suits small Bloom filters, e.g. client-side allowlists.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let bloom = ", stringify!($name), "::with_num_bits(256).items([1, 2].iter());")]
+            /// let s = bloom.to_url_safe();
+            /// assert!(s.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_'));
+            ///
+            #[doc = concat!("let new = ", stringify!($name), "::from_url_safe(&s).unwrap();")]
+            /// assert_eq!(bloom, new);
+            /// ```
+            pub fn to_url_safe(&self) -> String {
+                encode_url_safe(&self.to_bytes())
+            }
+
+            /// Reads a Bloom filter written by [`to_url_safe`](Self::to_url_safe).
+            ///
+            /// # Errors
+            /// Returns [`FromBytesError::InvalidBase64`] if `s` is not unpadded base64url, and otherwise the same errors
+            /// as [`from_bytes`](Self::from_bytes).
+            pub fn from_url_safe(s: &str) -> Result<Self, FromBytesError> {
+                Self::from_bytes(&decode_url_safe(s)?)
+            }
+        }
+    };
+}
+
+impl_bytes!(BloomFilter);
+impl_bytes!(AtomicBloomFilter);
+
+/// The base64url alphabet (RFC 4648, section 5).
+const BASE64_URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Encodes `bytes` as unpadded base64url: each 3 bytes become 4 characters, and a final 1 or 2 bytes become 2 or 3.
+fn encode_url_safe(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity((bytes.len() * 4 + 2) / 3);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            s.push(BASE64_URL[(n >> (18 - 6 * i)) as usize & 63] as char);
+        }
+    }
+    s
+}
+
+/// Inverse of [`encode_url_safe`]. Padding, other alphabets and non-zero trailing bits are rejected,
+/// so every Bloom filter has exactly one encoding.
+fn decode_url_safe(s: &str) -> Result<Vec<u8>, FromBytesError> {
+    if s.len() % 4 == 1 {
+        return Err(FromBytesError::InvalidBase64);
+    }
+    let mut bytes = Vec::with_capacity(s.len() / 4 * 3 + 2);
+    for chunk in s.as_bytes().chunks(4) {
+        let mut n = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let value = match c {
+                b'A'..=b'Z' => c - b'A',
+                b'a'..=b'z' => c - b'a' + 26,
+                b'0'..=b'9' => c - b'0' + 52,
+                b'-' => 62,
+                b'_' => 63,
+                _ => return Err(FromBytesError::InvalidBase64),
+            };
+            n |= (value as u32) << (18 - 6 * i);
+        }
+        let len = chunk.len() - 1;
+        if n & ((1 << (24 - 8 * len)) - 1) != 0 {
+            return Err(FromBytesError::InvalidBase64);
+        }
+        bytes.extend((0..len).map(|i| (n >> (16 - 8 * i)) as u8));
+    }
+    Ok(bytes)
+}
+
+/// Reads the parts of a Bloom filter written by `to_bytes`, checking the header.
+fn read_bytes(bytes: &[u8]) -> Result<(Vec<u64>, u32, DefaultHasher), FromBytesError> {
+    if bytes.get(..4) != Some(&MAGIC[..]) {
+        return Err(FromBytesError::BadMagic);
+    }
+    if bytes.len() < HEADER_LEN {
+        return Err(FromBytesError::InvalidLength);
+    }
+    if bytes[4] != VERSION {
+        return Err(FromBytesError::UnsupportedVersion(bytes[4]));
+    }
+    if bytes[5] != DEFAULT_HASHER {
+        return Err(FromBytesError::UnsupportedHasher(bytes[5]));
+    }
+    let num_hashes = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+    let num_bits = u64::from_le_bytes(bytes[12..20].try_into().unwrap());
+    let key0 = u64::from_le_bytes(bytes[20..28].try_into().unwrap());
+    let key1 = u64::from_le_bytes(bytes[28..36].try_into().unwrap());
+
+    let words = &bytes[HEADER_LEN..];
+    if num_bits % 64 != 0 || words.len() as u64 != num_bits / 8 {
+        return Err(FromBytesError::InvalidLength);
+    }
+    let bit_vec = words
+        .chunks_exact(8)
+        .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+        .collect();
+    Ok((bit_vec, num_hashes, DefaultHasher::with_keys(key0, key1)))
+}
+
+#[cfg(not(feature = "loom"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn round_trip() {
+        for num_bits in [64, 1024, 1 << 16] {
+            let mut bloom = BloomFilter::with_num_bits(num_bits).hashes(5);
+            bloom.extend(0..1000);
+            let new = BloomFilter::from_bytes(&bloom.to_bytes()).unwrap();
+            assert_eq!(bloom, new);
+            assert_eq!(new.num_hashes(), 5);
+            assert!((0..2000).all(|x| bloom.contains(&x) == new.contains(&x)));
+        }
+    }
+
+    #[test]
+    fn atomic_round_trip() {
+        for num_bits in [64, 1024, 1 << 16] {
+            let atomic = AtomicBloomFilter::with_num_bits(num_bits).hashes(5);
+            for x in 0..1000 {
+                atomic.insert(&x);
+            }
+            let new = AtomicBloomFilter::from_bytes(&atomic.to_bytes()).unwrap();
+            assert_eq!(atomic, new);
+            assert_eq!(new.num_hashes(), 5);
+            assert!((0..2000).all(|x| atomic.contains(&x) == new.contains(&x)));
+
+            let bloom = BloomFilter::from_bytes(&atomic.to_bytes()).unwrap();
+            assert_eq!(bloom.to_bytes(), atomic.to_bytes());
+            assert_eq!(
+                AtomicBloomFilter::from_bytes(&atomic.to_bytes()[..HEADER_LEN]),
+                Err(FromBytesError::InvalidLength)
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn write_to_streams() {
+        struct CountingWriter {
+            bytes: usize,
+            writes: usize,
+        }
+
+        impl std::io::Write for CountingWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                assert!(buf.len() <= HEADER_LEN);
+                self.bytes += buf.len();
+                self.writes += 1;
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let num_bits = 1 << 24;
+        let mut bloom = BloomFilter::with_num_bits(num_bits).hashes(4);
+        bloom.extend(0..100_000);
+        let mut writer = CountingWriter {
+            bytes: 0,
+            writes: 0,
+        };
+        bloom.write_to(&mut writer).unwrap();
+        assert_eq!(writer.bytes, bloom.serialized_size());
+        assert_eq!(writer.bytes, HEADER_LEN + num_bits / 8);
+        assert_eq!(writer.writes, 1 + num_bits / 64);
+
+        let mut bytes = Vec::new();
+        bloom.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes, bloom.to_bytes());
+    }
+
+    #[test]
+    fn url_safe_round_trip() {
+        for num_bits in [64, 128, 192, 1024] {
+            let mut bloom = BloomFilter::with_num_bits(num_bits).hashes(3);
+            bloom.extend(0..20);
+            let s = bloom.to_url_safe();
+            assert_eq!(s.len(), (bloom.serialized_size() * 4 + 2) / 3);
+            assert_eq!(BloomFilter::from_url_safe(&s).unwrap(), bloom);
+
+            let atomic = AtomicBloomFilter::from_url_safe(&s).unwrap();
+            assert_eq!(atomic.to_url_safe(), s);
+        }
+        for len in 0..10 {
+            let bytes: Vec<u8> = (0..len).map(|x| (x * 97 + 13) as u8).collect();
+            assert_eq!(decode_url_safe(&encode_url_safe(&bytes)).unwrap(), bytes);
+        }
+        assert_eq!(encode_url_safe(b"\xfb\xff\xbf"), "-_-_");
+    }
+
+    #[test]
+    fn rejects_malformed_url_safe() {
+        let bloom = BloomFilter::with_num_bits(64).hashes(3);
+        let s = bloom.to_url_safe();
+        // 36 + 8 bytes encode to 59 characters, whose last 2 bits must be zero.
+        assert_eq!(s.len(), 59);
+        assert!(s.ends_with('A'));
+
+        for bad in [
+            format!("{s}="),
+            format!("{s}AA"),
+            s.replacen('A', "+", 1),
+            s.replacen('A', "/", 1),
+            s.replacen('A', " ", 1),
+            format!("{}B", &s[..s.len() - 1]),
+        ] {
+            assert_eq!(
+                BloomFilter::from_url_safe(&bad),
+                Err(FromBytesError::InvalidBase64),
+                "{bad}"
+            );
+        }
+        assert_eq!(
+            BloomFilter::from_url_safe(&s[..s.len() - 1]),
+            Err(FromBytesError::InvalidLength)
+        );
+        assert_eq!(
+            BloomFilter::from_url_safe("not-a-Bloom-filter-1234"),
+            Err(FromBytesError::BadMagic)
+        );
+    }
+
+    #[test]
+    fn little_endian() {
+        let bloom = BloomFilter::from_vec(vec![0x0102_0304_0506_0708, 1])
+            .seed(&1)
+            .hashes(3);
+        let bytes = bloom.to_bytes();
+        assert_eq!(&bytes[8..12], &[3, 0, 0, 0]);
+        assert_eq!(&bytes[12..20], &[128, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&bytes[36..44], &[8, 7, 6, 5, 4, 3, 2, 1]);
+        assert_eq!(&bytes[44..], &[1, 0, 0, 0, 0, 0, 0, 0]);
+
+        // A writer using native-endian words on a big-endian machine would produce swapped words,
+        // which must not read back as the same Bloom filter.
+        let mut swapped = bytes.clone();
+        for word in swapped[HEADER_LEN..].chunks_exact_mut(8) {
+            word.reverse();
+        }
+        let new = BloomFilter::from_bytes(&swapped).unwrap();
+        assert_ne!(bloom, new);
+        assert_eq!(
+            new.iter().map(u64::swap_bytes).collect::<Vec<_>>(),
+            bloom.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_bytes() {
+        let mut bloom = BloomFilter::with_num_bits(1024).hashes(4);
+        bloom.extend(0..100);
+        let bytes = bloom.to_bytes();
+
+        for len in [0, 3] {
+            assert_eq!(
+                BloomFilter::from_bytes(&bytes[..len]),
+                Err(FromBytesError::BadMagic)
+            );
+        }
+        // A truncated header still starts with the magic number.
+        for len in [
+            4,
+            12,
+            HEADER_LEN - 1,
+            HEADER_LEN,
+            HEADER_LEN + 1,
+            bytes.len() - 1,
+        ] {
+            assert_eq!(
+                BloomFilter::from_bytes(&bytes[..len]),
+                Err(FromBytesError::InvalidLength)
+            );
+        }
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert_eq!(
+            BloomFilter::from_bytes(&longer),
+            Err(FromBytesError::InvalidLength)
+        );
+
+        let garbage: Vec<u8> = (0..200).map(|x| (x * 37) as u8).collect();
+        assert_eq!(
+            BloomFilter::from_bytes(&garbage),
+            Err(FromBytesError::BadMagic)
+        );
+
+        let mut corrupt = bytes.clone();
+        corrupt[4] = 2;
+        assert_eq!(
+            BloomFilter::from_bytes(&corrupt),
+            Err(FromBytesError::UnsupportedVersion(2))
+        );
+        let mut corrupt = bytes.clone();
+        corrupt[5] = 7;
+        assert_eq!(
+            BloomFilter::from_bytes(&corrupt),
+            Err(FromBytesError::UnsupportedHasher(7))
+        );
+        let mut corrupt = bytes.clone();
+        corrupt[8..12].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(
+            BloomFilter::from_bytes(&corrupt),
+            Err(FromBytesError::InvalidFilter(BuildError::ZeroHashes))
+        );
+        let empty = [&bytes[..12], &[0; 8], &bytes[20..HEADER_LEN]].concat();
+        assert_eq!(
+            BloomFilter::from_bytes(&empty),
+            Err(FromBytesError::InvalidFilter(BuildError::EmptyBitVec))
+        );
+    }
+}
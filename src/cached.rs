@@ -0,0 +1,151 @@
+use crate::{BloomFilter, BuildHasher, DefaultHasher};
+use alloc::collections::VecDeque;
+use core::hash::Hash;
+
+/// A [`BloomFilter`] fronted by a small cache of recent negative lookups.
+///
+/// Workloads that repeatedly check the same few absent items pay for hashing and probing on every
+/// call. [`CachedBloomFilter`] remembers up to `capacity` items that were most recently found to be
+/// absent, so repeated checks only compare against the cached items. Items are evicted in least
+/// recently used order, and an item is removed from the cache when it is inserted.
+///
+/// The cache compares items with [`Eq`], so it only pays off when that is cheaper than hashing
+/// and `capacity` is small.
+///
+/// # Examples
+/// ```
+/// use fastbloom::{BloomFilter, CachedBloomFilter};
+///
+/// let bloom = BloomFilter::with_num_bits(1024).expected_items(10);
+/// let mut filter = CachedBloomFilter::new(bloom, 4);
+/// assert!(!filter.contains(&"absent"));
+/// assert!(!filter.contains(&"absent"));
+/// assert_eq!(filter.cache_hits(), 1);
+///
+/// filter.insert(&"absent");
+/// assert!(filter.contains(&"absent"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CachedBloomFilter<T, S = DefaultHasher> {
+    bloom: BloomFilter<S>,
+    negatives: VecDeque<T>,
+    capacity: usize,
+    cache_hits: u64,
+}
+
+impl<T: Hash + Eq + Clone, S: BuildHasher> CachedBloomFilter<T, S> {
+    /// Wraps `bloom`, caching up to `capacity` negative lookups.
+    pub fn new(bloom: BloomFilter<S>, capacity: usize) -> Self {
+        Self {
+            bloom,
+            negatives: VecDeque::with_capacity(capacity),
+            capacity,
+            cache_hits: 0,
+        }
+    }
+
+    /// Checks if an element is possibly in the Bloom filter, consulting the negative cache first.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
+    pub fn contains(&mut self, val: &T) -> bool {
+        if let Some(i) = self.negatives.iter().position(|x| x == val) {
+            self.cache_hits += 1;
+            if let Some(x) = self.negatives.remove(i) {
+                self.negatives.push_front(x);
+            }
+            return false;
+        }
+        let contained = self.bloom.contains(val);
+        if !contained && self.capacity > 0 {
+            if self.negatives.len() == self.capacity {
+                self.negatives.pop_back();
+            }
+            self.negatives.push_front(val.clone());
+        }
+        contained
+    }
+
+    /// Inserts an element into the Bloom filter, removing it from the negative cache.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
+    /// `false` otherwise.
+    pub fn insert(&mut self, val: &T) -> bool {
+        self.negatives.retain(|x| x != val);
+        self.bloom.insert(val)
+    }
+
+    /// Clears the Bloom filter and the negative cache.
+    pub fn clear(&mut self) {
+        self.negatives.clear();
+        self.bloom.clear();
+    }
+
+    /// Returns the number of calls to [`Self::contains`] answered by the negative cache.
+    #[inline]
+    pub fn cache_hits(&self) -> u64 {
+        self.cache_hits
+    }
+
+    /// Returns the underlying [`BloomFilter`].
+    #[inline]
+    pub fn as_bloom(&self) -> &BloomFilter<S> {
+        &self.bloom
+    }
+
+    /// Consumes `self`, returning the underlying [`BloomFilter`].
+    #[inline]
+    pub fn into_bloom(self) -> BloomFilter<S> {
+        self.bloom
+    }
+}
+
+#[cfg(not(feature = "loom"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_negatives_hit_cache() {
+        let bloom = BloomFilter::with_num_bits(1 << 16)
+            .seed(&1)
+            .expected_items(100);
+        let mut filter = CachedBloomFilter::new(bloom, 2);
+        for x in 0..100u64 {
+            filter.insert(&x);
+        }
+        let absent = (1000..).find(|x| !filter.as_bloom().contains(x)).unwrap();
+        for _ in 0..10 {
+            assert!(!filter.contains(&absent));
+        }
+        assert_eq!(filter.cache_hits(), 9);
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let bloom = BloomFilter::with_num_bits(1 << 16).seed(&1).hashes(4);
+        let mut filter = CachedBloomFilter::new(bloom, 2);
+        filter.contains(&1);
+        filter.contains(&2);
+        filter.contains(&1);
+        filter.contains(&3);
+        assert_eq!(filter.cache_hits(), 1);
+        filter.contains(&1);
+        assert_eq!(filter.cache_hits(), 2);
+        filter.contains(&2);
+        assert_eq!(filter.cache_hits(), 2);
+    }
+
+    #[test]
+    fn insert_invalidates_cache() {
+        let bloom = BloomFilter::with_num_bits(1 << 16).seed(&1).hashes(4);
+        let mut filter = CachedBloomFilter::new(bloom, 2);
+        assert!(!filter.contains(&1));
+        filter.insert(&1);
+        assert!(filter.contains(&1));
+        assert_eq!(filter.cache_hits(), 0);
+    }
+}
@@ -0,0 +1,668 @@
+use crate::{
+    index, AtomicBuilderWithBits, AtomicBuilderWithFalsePositiveRate, AtomicU8, BuildHasher,
+    BuilderWithBits, BuilderWithFalsePositiveRate, DefaultHasher, DoubleHasher,
+};
+use alloc::{boxed::Box, vec};
+use core::{cmp::max, hash::Hash, sync::atomic::Ordering::Relaxed};
+
+/// The number of bits per counter.
+const COUNTER_BITS: usize = 4;
+/// The number of counters packed into a `u64`.
+const COUNTERS_PER_WORD: usize = 64 / COUNTER_BITS;
+/// The largest value of a counter. Saturated counters are never decremented.
+const MAX_COUNT: u64 = (1 << COUNTER_BITS) - 1;
+
+/// Implements the builder methods that construct `$counting` instead of `$bloom`.
+///
+/// A counting Bloom filter has a counter per bit of the Bloom filter the builder would construct, and its number
+/// of hashes and hasher, so both meet the same false positive rate.
+macro_rules! counting_builders {
+    ($counting:ident, $bloom:ident, $bits:ident, $fp:ident) => {
+        impl<S: BuildHasher> $bits<S> {
+            #[doc = concat!("\"Consumes\" this builder, using the provided `num_hashes` to return an empty [`", stringify!($counting), "`]")]
+            /// with a counter per bit.
+            ///
+            /// Note: if `num_hashes` is 0, it is treated as 1.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($bloom), ";")]
+            ///
+            #[doc = concat!("let filter = ", stringify!($bloom), "::with_num_bits(4096).seed(&1).counting_hashes(4);")]
+            /// assert_eq!(filter.num_counters(), 4096);
+            /// assert_eq!(filter.num_hashes(), 4);
+            /// ```
+            pub fn counting_hashes(self, num_hashes: u32) -> $counting<S> {
+                $counting::new(self.num_bits(), num_hashes, self.hasher)
+            }
+        }
+
+        impl<S: BuildHasher> $fp<S> {
+            #[doc = concat!("\"Consumes\" this builder, using the provided `expected_items` to return an empty [`", stringify!($counting), "`]")]
+            #[doc = concat!("with a counter per bit of the [`", stringify!($bloom), "`](crate::", stringify!($bloom), ") that [`expected_items`](Self::expected_items) would return,")]
+            /// so it meets the same false positive rate.
+            ///
+            /// Note: `expected_items` will internally be set to 1 if 0 is specified.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($bloom), ";")]
+            ///
+            #[doc = concat!("let filter = ", stringify!($bloom), "::with_false_pos(0.001).seed(&1).counting_expected_items(500);")]
+            /// ```
+            pub fn counting_expected_items(self, expected_items: usize) -> $counting<S> {
+                let builder = self.into_bits_builder(expected_items);
+                let num_hashes = builder.optimal_hashes(expected_items);
+                builder.counting_hashes(num_hashes)
+            }
+        }
+    };
+}
+
+/// A Bloom filter that supports removing items, using a 4-bit counter instead of a bit per position.
+///
+/// Inserting an item increments its counters and [`remove`](Self::remove) decrements them,
+/// so an item is contained while all of its counters are non-zero. A counter that reaches 15 is saturated:
+/// its true count is unknown, so it is never decremented again, trading a little accuracy for never
+/// producing false negatives.
+///
+/// A [`CountingBloomFilter`] is built with [`BloomFilter`](crate::BloomFilter)'s builders, ending in
+/// [`counting_expected_items`](BuilderWithFalsePositiveRate::counting_expected_items) or
+/// [`counting_hashes`](BuilderWithBits::counting_hashes). It has a counter per bit of the [`BloomFilter`](crate::BloomFilter)
+/// the builder would construct, and the same number of hashes and hasher, so it meets the same false positive rate
+/// while using 4 times the memory.
+///
+/// # Examples
+/// ```
+/// use fastbloom::BloomFilter;
+///
+/// let mut filter = BloomFilter::with_false_pos(0.001).counting_expected_items(1000);
+/// filter.insert(&42);
+/// assert!(filter.contains(&42));
+/// filter.remove(&42);
+/// assert!(!filter.contains(&42));
+/// ```
+/// The builders set the size, seed or hasher as usual:
+/// ```
+/// use fastbloom::BloomFilter;
+///
+/// let filter = BloomFilter::with_num_bits(4096).seed(&42).counting_hashes(4);
+/// assert_eq!(filter.num_counters(), 4096);
+/// assert_eq!(filter.num_hashes(), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CountingBloomFilter<S = DefaultHasher> {
+    counters: Box<[u64]>,
+    num_hashes: u32,
+    hasher: S,
+}
+
+counting_builders!(
+    CountingBloomFilter,
+    BloomFilter,
+    BuilderWithBits,
+    BuilderWithFalsePositiveRate
+);
+
+impl<S: BuildHasher> CountingBloomFilter<S> {
+    /// Creates an empty [`CountingBloomFilter`] with at least `num_counters` counters.
+    fn new(num_counters: usize, num_hashes: u32, hasher: S) -> Self {
+        let num_words = (num_counters + COUNTERS_PER_WORD - 1) / COUNTERS_PER_WORD;
+        Self {
+            counters: vec![0; num_words].into(),
+            num_hashes: max(1, num_hashes),
+            hasher,
+        }
+    }
+
+    /// Inserts an element into the Bloom filter, incrementing its counters.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
+    /// `false` otherwise.
+    #[inline]
+    pub fn insert(&mut self, val: &(impl Hash + ?Sized)) -> bool {
+        let mut previously_contained = true;
+        for i in self.indices(self.source_hash(val)) {
+            previously_contained &= self.count(i) > 0;
+            self.increment(i);
+        }
+        previously_contained
+    }
+
+    /// Removes an element from the Bloom filter, decrementing its counters.
+    ///
+    /// Items that are not contained are left alone, since decrementing their counters could remove other items.
+    /// Removing an item that was never inserted but is a false positive can still remove other items,
+    /// so only remove items that were inserted.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item was possibly in the Bloom filter and was removed, `false` otherwise.
+    #[inline]
+    pub fn remove(&mut self, val: &(impl Hash + ?Sized)) -> bool {
+        let hash = self.source_hash(val);
+        if !self.contains_hash(hash) {
+            return false;
+        }
+        for i in self.indices(hash) {
+            self.decrement(i);
+        }
+        true
+    }
+
+    /// Checks if an element is possibly in the Bloom filter.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
+    #[inline]
+    pub fn contains(&self, val: &(impl Hash + ?Sized)) -> bool {
+        self.contains_hash(self.source_hash(val))
+    }
+
+    #[inline]
+    fn contains_hash(&self, hash: u64) -> bool {
+        self.indices(hash).all(|i| self.count(i) > 0)
+    }
+
+    /// Returns the number of hashes per item.
+    #[inline]
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    /// Returns the number of counters.
+    #[inline]
+    pub fn num_counters(&self) -> usize {
+        self.counters.len() * COUNTERS_PER_WORD
+    }
+
+    /// Returns the estimated number of items in the Bloom filter, counting repeated inserts: the sum of all counters
+    /// divided by the number of hashes.
+    ///
+    /// Each insert adds `num_hashes` to the sum and each remove subtracts it, so the estimate is exact until counters
+    /// saturate at 15; saturated counters stop counting, so the estimate is then too low.
+    ///
+    /// # Examples
+    /// ```
+    /// use fastbloom::BloomFilter;
+    ///
+    /// let mut filter = BloomFilter::with_false_pos(0.001).counting_expected_items(1000);
+    /// filter.extend([1, 2, 2, 3]);
+    /// assert_eq!(filter.estimated_len(), 4);
+    /// ```
+    pub fn estimated_len(&self) -> usize {
+        let sum: u64 = (0..self.num_counters()).map(|i| self.count(i)).sum();
+        (sum / self.num_hashes as u64) as usize
+    }
+
+    /// Clear all of the counters, removing all items.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.counters.iter_mut().for_each(|x| *x = 0);
+    }
+
+    #[inline]
+    fn source_hash(&self, val: &(impl Hash + ?Sized)) -> u64 {
+        let mut state = self.hasher.build_hasher();
+        val.hash(&mut state);
+        core::hash::Hasher::finish(&state)
+    }
+
+    /// Returns the counter index of each of the item's hashes, probing like [`BloomFilter`](crate::BloomFilter) does.
+    #[inline]
+    fn indices(&self, hash: u64) -> impl Iterator<Item = usize> {
+        let num_counters = self.num_counters();
+        let mut hasher = DoubleHasher::new(hash);
+        core::iter::once(index(num_counters, hash))
+            .chain((1..self.num_hashes).map(move |_| index(num_counters, hasher.next())))
+    }
+
+    #[inline]
+    fn count(&self, i: usize) -> u64 {
+        let (word, shift) = coord(i);
+        (self.counters[word] >> shift) & MAX_COUNT
+    }
+
+    #[inline]
+    fn increment(&mut self, i: usize) {
+        if self.count(i) < MAX_COUNT {
+            let (word, shift) = coord(i);
+            self.counters[word] += 1 << shift;
+        }
+    }
+
+    #[inline]
+    fn decrement(&mut self, i: usize) {
+        let count = self.count(i);
+        if count > 0 && count < MAX_COUNT {
+            let (word, shift) = coord(i);
+            self.counters[word] -= 1 << shift;
+        }
+    }
+}
+
+impl<T: Hash, S: BuildHasher> Extend<T> for CountingBloomFilter<S> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.insert(&val);
+        }
+    }
+}
+
+/// Returns the word and the shift of the counter at index `i`.
+#[inline]
+fn coord(i: usize) -> (usize, usize) {
+    (
+        i / COUNTERS_PER_WORD,
+        (i % COUNTERS_PER_WORD) * COUNTER_BITS,
+    )
+}
+
+/// The largest value of an [`AtomicCountingBloomFilter`] counter. Saturated counters are never decremented.
+const ATOMIC_MAX_COUNT: u8 = u8::MAX;
+
+/// A concurrent Bloom filter that supports removing items, using an 8-bit atomic counter per position.
+///
+/// [`insert`](Self::insert) and [`remove`](Self::remove) take `&self` and update counters with atomic
+/// read-modify-write operations, so an [`AtomicCountingBloomFilter`] can be shared between threads without a lock.
+/// Like [`CountingBloomFilter`], a counter that reaches 255 is saturated and never decremented again,
+/// so concurrent updates never produce false negatives for items that are still inserted.
+///
+/// Each counter is updated atomically, but an item's counters are not updated together: a concurrent
+/// [`contains`](Self::contains) may see an item that is being inserted or removed as partially present.
+/// As with [`CountingBloomFilter`], only remove items that were inserted, once per insert.
+///
+/// An [`AtomicCountingBloomFilter`] is built with [`AtomicBloomFilter`](crate::AtomicBloomFilter)'s builders, ending in
+/// [`counting_expected_items`](AtomicBuilderWithFalsePositiveRate::counting_expected_items) or
+/// [`counting_hashes`](AtomicBuilderWithBits::counting_hashes), like a [`CountingBloomFilter`].
+/// It uses 8 times the memory of the [`AtomicBloomFilter`](crate::AtomicBloomFilter) the builder would construct.
+///
+/// # Examples
+/// ```
+/// use fastbloom::AtomicBloomFilter;
+///
+/// let filter = AtomicBloomFilter::with_false_pos(0.001).counting_expected_items(1000);
+/// filter.insert(&42);
+/// assert!(filter.contains(&42));
+/// filter.remove(&42);
+/// assert!(!filter.contains(&42));
+/// ```
+#[derive(Debug)]
+pub struct AtomicCountingBloomFilter<S = DefaultHasher> {
+    counters: Box<[AtomicU8]>,
+    num_hashes: u32,
+    hasher: S,
+}
+
+counting_builders!(
+    AtomicCountingBloomFilter,
+    AtomicBloomFilter,
+    AtomicBuilderWithBits,
+    AtomicBuilderWithFalsePositiveRate
+);
+
+impl<S: BuildHasher> AtomicCountingBloomFilter<S> {
+    /// Creates an empty [`AtomicCountingBloomFilter`] with `num_counters` counters.
+    fn new(num_counters: usize, num_hashes: u32, hasher: S) -> Self {
+        Self {
+            counters: (0..num_counters).map(|_| AtomicU8::new(0)).collect(),
+            num_hashes: max(1, num_hashes),
+            hasher,
+        }
+    }
+
+    /// Inserts an element into the Bloom filter, incrementing its counters.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
+    /// `false` otherwise.
+    #[inline]
+    pub fn insert(&self, val: &(impl Hash + ?Sized)) -> bool {
+        let mut previously_contained = true;
+        for i in self.indices(self.source_hash(val)) {
+            previously_contained &= self.increment(i) > 0;
+        }
+        previously_contained
+    }
+
+    /// Removes an element from the Bloom filter, decrementing its counters.
+    ///
+    /// Items that are not contained are left alone, since decrementing their counters could remove other items.
+    /// Removing an item that was never inserted but is a false positive can still remove other items,
+    /// so only remove items that were inserted.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item was possibly in the Bloom filter and was removed, `false` otherwise.
+    #[inline]
+    pub fn remove(&self, val: &(impl Hash + ?Sized)) -> bool {
+        let hash = self.source_hash(val);
+        if !self.contains_hash(hash) {
+            return false;
+        }
+        for i in self.indices(hash) {
+            self.decrement(i);
+        }
+        true
+    }
+
+    /// Checks if an element is possibly in the Bloom filter.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
+    #[inline]
+    pub fn contains(&self, val: &(impl Hash + ?Sized)) -> bool {
+        self.contains_hash(self.source_hash(val))
+    }
+
+    #[inline]
+    fn contains_hash(&self, hash: u64) -> bool {
+        self.indices(hash).all(|i| self.count(i) > 0)
+    }
+
+    /// Returns the number of hashes per item.
+    #[inline]
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    /// Returns the number of counters.
+    #[inline]
+    pub fn num_counters(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// Returns the estimated number of items in the Bloom filter, counting repeated inserts: the sum of all counters
+    /// divided by the number of hashes.
+    ///
+    /// The estimate is exact until counters saturate at 255, and too low afterwards.
+    /// Counters are read one at a time, so concurrent inserts and removes may be partially reflected.
+    pub fn estimated_len(&self) -> usize {
+        let sum: u64 = (0..self.num_counters()).map(|i| self.count(i) as u64).sum();
+        (sum / self.num_hashes as u64) as usize
+    }
+
+    /// Clear all of the counters, removing all items.
+    ///
+    /// Counters are zeroed one at a time, so concurrent inserts may survive a concurrent clear.
+    #[inline]
+    pub fn clear(&self) {
+        self.counters.iter().for_each(|x| x.store(0, Relaxed));
+    }
+
+    #[inline]
+    fn source_hash(&self, val: &(impl Hash + ?Sized)) -> u64 {
+        let mut state = self.hasher.build_hasher();
+        val.hash(&mut state);
+        core::hash::Hasher::finish(&state)
+    }
+
+    /// Returns the counter index of each of the item's hashes, probing like [`AtomicBloomFilter`](crate::AtomicBloomFilter) does.
+    #[inline]
+    fn indices(&self, hash: u64) -> impl Iterator<Item = usize> {
+        let num_counters = self.num_counters();
+        let mut hasher = DoubleHasher::new(hash);
+        core::iter::once(index(num_counters, hash))
+            .chain((1..self.num_hashes).map(move |_| index(num_counters, hasher.next())))
+    }
+
+    #[inline]
+    fn count(&self, i: usize) -> u8 {
+        self.counters[i].load(Relaxed)
+    }
+
+    /// Increments the counter unless it is saturated, returning its previous value.
+    #[inline]
+    fn increment(&self, i: usize) -> u8 {
+        match self.counters[i].fetch_update(Relaxed, Relaxed, |count| {
+            (count < ATOMIC_MAX_COUNT).then(|| count + 1)
+        }) {
+            Ok(count) | Err(count) => count,
+        }
+    }
+
+    /// Decrements the counter unless it is zero or saturated.
+    #[inline]
+    fn decrement(&self, i: usize) {
+        let _ = self.counters[i].fetch_update(Relaxed, Relaxed, |count| {
+            (count > 0 && count < ATOMIC_MAX_COUNT).then(|| count - 1)
+        });
+    }
+}
+
+impl<S: Clone> Clone for AtomicCountingBloomFilter<S> {
+    /// Copies each counter with a relaxed load, so the clone may miss concurrent updates.
+    fn clone(&self) -> Self {
+        Self {
+            counters: self
+                .counters
+                .iter()
+                .map(|x| AtomicU8::new(x.load(Relaxed)))
+                .collect(),
+            num_hashes: self.num_hashes,
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+
+impl<T: Hash, S: BuildHasher> Extend<T> for AtomicCountingBloomFilter<S> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.insert(&val);
+        }
+    }
+}
+
+#[cfg(not(feature = "loom"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AtomicBloomFilter, BloomFilter};
+
+    fn filter(num_items: usize) -> CountingBloomFilter {
+        BloomFilter::with_false_pos(0.001)
+            .seed(&1)
+            .counting_expected_items(num_items)
+    }
+
+    #[test]
+    fn insert_remove_round_trip() {
+        let mut filter = filter(1000);
+        filter.extend(0..1000u64);
+        assert!((0..1000u64).all(|x| filter.contains(&x)));
+
+        for x in 0..500u64 {
+            assert!(filter.remove(&x));
+        }
+        assert!((500..1000u64).all(|x| filter.contains(&x)));
+        let num_remaining = (0..500u64).filter(|x| filter.contains(x)).count();
+        assert!(num_remaining < 5, "{num_remaining}");
+
+        for x in 500..1000u64 {
+            filter.remove(&x);
+        }
+        assert!(filter.counters.iter().all(|&x| x == 0));
+    }
+
+    #[test]
+    fn probes_match_bloom_filter() {
+        let mut expected = BloomFilter::with_num_bits(4096).seed(&1).hashes(5);
+        let mut filter = BloomFilter::with_num_bits(4096).seed(&1).counting_hashes(5);
+        for x in 0..100u64 {
+            expected.insert(&x);
+            filter.insert(&x);
+        }
+        for i in 0..filter.num_counters() {
+            let (word, bit) = (i / 64, i % 64);
+            assert_eq!(
+                filter.count(i) > 0,
+                expected.as_slice()[word] & (1 << bit) != 0
+            );
+        }
+    }
+
+    #[test]
+    fn sized_like_bloom_filter() {
+        for num_items in [0, 1, 100, 10_000] {
+            let bloom = BloomFilter::with_false_pos(0.01).expected_items(num_items);
+            let filter = BloomFilter::with_false_pos(0.01).counting_expected_items(num_items);
+            assert_eq!(filter.num_counters(), bloom.num_bits());
+            assert_eq!(filter.num_hashes(), bloom.num_hashes());
+        }
+        let filter = BloomFilter::with_num_bits(1024).counting_hashes(0);
+        assert_eq!(filter.num_hashes(), 1);
+        let filter = AtomicBloomFilter::with_num_bits(1024).counting_hashes(0);
+        assert_eq!(filter.num_counters(), 1024);
+        assert_eq!(filter.num_hashes(), 1);
+    }
+
+    #[test]
+    fn uses_builder_hasher() {
+        let hasher = DefaultHasher::seeded(&[7; 16]);
+        let filter = BloomFilter::with_num_bits(1024)
+            .hasher(hasher.clone())
+            .counting_hashes(3);
+        assert_eq!(filter.num_counters(), 1024);
+        assert_eq!(filter.source_hash(&1), {
+            let mut state = hasher.build_hasher();
+            1.hash(&mut state);
+            core::hash::Hasher::finish(&state)
+        });
+    }
+
+    #[test]
+    fn duplicates_need_matching_removes() {
+        let mut filter = filter(100);
+        filter.insert(&1);
+        filter.insert(&1);
+        filter.remove(&1);
+        assert!(filter.contains(&1));
+        filter.remove(&1);
+        assert!(!filter.contains(&1));
+        assert!(!filter.remove(&1));
+    }
+
+    #[test]
+    fn estimated_len_counts_repeats() {
+        let mut filter = filter(1000);
+        let atomic = atomic_filter(1000);
+        for x in 0..100u64 {
+            for _ in 0..=x % 3 {
+                filter.insert(&x);
+                atomic.insert(&x);
+            }
+        }
+        assert_eq!(filter.estimated_len(), 199);
+        assert_eq!(atomic.estimated_len(), 199);
+        for x in 0..50u64 {
+            filter.remove(&x);
+            atomic.remove(&x);
+        }
+        assert_eq!(filter.estimated_len(), 149);
+        assert_eq!(atomic.estimated_len(), 149);
+        filter.clear();
+        assert_eq!(filter.estimated_len(), 0);
+    }
+
+    #[test]
+    fn saturated_counters_stay() {
+        let mut filter = filter(100);
+        for _ in 0..20 {
+            filter.insert(&1);
+        }
+        for i in filter.indices(filter.source_hash(&1)) {
+            assert_eq!(filter.count(i), MAX_COUNT);
+        }
+        for _ in 0..20 {
+            assert!(filter.remove(&1));
+        }
+        assert!(filter.contains(&1));
+    }
+
+    #[test]
+    fn counters_are_independent() {
+        let mut filter = filter(100);
+        for i in 0..filter.num_counters() {
+            for _ in 0..(i as u64 % 17) {
+                filter.increment(i);
+            }
+        }
+        for i in 0..filter.num_counters() {
+            assert_eq!(filter.count(i), (i as u64 % 17).min(MAX_COUNT));
+        }
+    }
+
+    fn atomic_filter(num_items: usize) -> AtomicCountingBloomFilter {
+        AtomicBloomFilter::with_false_pos(0.001)
+            .seed(&1)
+            .counting_expected_items(num_items)
+    }
+
+    #[test]
+    fn atomic_matches_counting() {
+        let atomic = atomic_filter(1000);
+        let mut counting = filter(1000);
+        assert_eq!(atomic.num_counters(), counting.num_counters());
+        for x in 0..1000u64 {
+            assert_eq!(atomic.insert(&x), counting.insert(&x));
+        }
+        for x in 0..500u64 {
+            assert_eq!(atomic.remove(&x), counting.remove(&x));
+        }
+        for i in 0..atomic.num_counters() {
+            assert_eq!(atomic.count(i) > 0, counting.count(i) > 0);
+        }
+        for x in 0..10_000u64 {
+            assert_eq!(atomic.contains(&x), counting.contains(&x));
+        }
+    }
+
+    #[test]
+    fn atomic_saturated_counters_stay() {
+        let filter = atomic_filter(100);
+        for _ in 0..300 {
+            filter.insert(&1);
+        }
+        for i in filter.indices(filter.source_hash(&1)) {
+            assert_eq!(filter.count(i), ATOMIC_MAX_COUNT);
+        }
+        for _ in 0..300 {
+            assert!(filter.remove(&1));
+        }
+        assert!(filter.contains(&1));
+        filter.clear();
+        assert!(!filter.contains(&1));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn atomic_concurrent_insert_remove() {
+        let filter = atomic_filter(4000);
+        std::thread::scope(|s| {
+            for t in 0..4u64 {
+                let filter = &filter;
+                s.spawn(move || {
+                    let items = t * 1000..(t + 1) * 1000;
+                    for x in items.clone() {
+                        filter.insert(&x);
+                    }
+                    for x in items.clone().step_by(2) {
+                        assert!(filter.remove(&x));
+                    }
+                });
+            }
+        });
+        assert!((1..4000u64).step_by(2).all(|x| filter.contains(&x)));
+        for x in (1..4000u64).step_by(2) {
+            filter.remove(&x);
+        }
+        assert!(filter.counters.iter().all(|x| x.load(Relaxed) == 0));
+    }
+}
@@ -0,0 +1,522 @@
+use crate::{
+    BloomFilter, BuildHasher, BuilderWithBits, BuilderWithFalsePositiveRate, DefaultHasher,
+};
+use alloc::vec::Vec;
+use core::hash::Hash;
+
+/// A Bloom filter whose items expire after a number of ticks, for "seen recently" checks.
+///
+/// A [`DecayingBloomFilter`] is a ring of `num_generations` Bloom filters. Items are inserted into the current
+/// generation, and [`contains`](Self::contains) checks all of them. Each call to [`tick`](Self::tick) clears the oldest
+/// generation and makes it the current one, so an item expires on the `num_generations`-th tick after it was last
+/// inserted. Call [`tick`](Self::tick) on a timer to expire items after a duration,
+/// e.g. with 6 generations and a tick every 10 seconds, items are kept for 50 to 60 seconds.
+///
+/// More generations make expiry more precise, at the cost of memory and a higher false positive rate:
+/// every generation is a Bloom filter of its own, so the false positive rate is at most the sum of theirs.
+/// All generations share the same hasher, so items are hashed only once.
+///
+/// Generations are numbered by the ticks so far, see [`generation`](Self::generation).
+/// [`insert_at`](Self::insert_at) inserts into an older generation, and [`contains_since`](Self::contains_since)
+/// checks only the generations since a given one.
+///
+/// A [`DecayingBloomFilter`] is built with [`BloomFilter`]'s builders, ending in
+/// [`decaying_expected_items`](BuilderWithFalsePositiveRate::decaying_expected_items) or
+/// [`decaying_hashes`](BuilderWithBits::decaying_hashes).
+///
+/// # Examples
+/// ```
+/// use fastbloom::BloomFilter;
+///
+/// let mut filter = BloomFilter::with_num_bits(1024).decaying_hashes(4, 2);
+/// filter.insert(&42);
+/// filter.tick();
+/// assert!(filter.contains(&42));
+/// filter.tick();
+/// assert!(!filter.contains(&42));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DecayingBloomFilter<S = DefaultHasher> {
+    generations: Vec<BloomFilter<S>>,
+    /// The generation new items are inserted into, incremented by each tick.
+    generation: u64,
+}
+
+impl<S: BuildHasher + Clone> BuilderWithBits<S> {
+    /// "Consumes" this builder, using the provided `num_hashes` to return an empty [`DecayingBloomFilter`]
+    /// with `num_generations` generations, each with the number of bits of this builder.
+    ///
+    /// Size the generations for the items inserted between two ticks.
+    /// Bits already set in this builder, e.g. from [`BloomFilter::from_vec`], are in the current generation,
+    /// and expire with it.
+    ///
+    /// Note: if `num_hashes` is 0, it is treated as 1.
+    ///
+    /// # Panics
+    /// Panics if `num_generations` is 0.
+    ///
+    /// # Examples
+    /// ```
+    /// use fastbloom::BloomFilter;
+    ///
+    /// let filter = BloomFilter::with_num_bits(1024).seed(&1).decaying_hashes(4, 6);
+    /// assert_eq!(filter.num_generations(), 6);
+    /// ```
+    pub fn decaying_hashes(
+        self,
+        num_hashes: u32,
+        num_generations: usize,
+    ) -> DecayingBloomFilter<S> {
+        assert!(
+            num_generations > 0,
+            "num_generations must be greater than 0"
+        );
+        let (num_bits, hasher) = (self.num_bits(), self.hasher.clone());
+        let generations = core::iter::once(self.hashes(num_hashes))
+            .chain((1..num_generations).map(|_| {
+                BloomFilter::new_builder(num_bits)
+                    .hasher(hasher.clone())
+                    .hashes(num_hashes)
+            }))
+            .collect();
+        DecayingBloomFilter {
+            generations,
+            generation: 0,
+        }
+    }
+}
+
+impl<S: BuildHasher + Clone> BuilderWithFalsePositiveRate<S> {
+    /// "Consumes" this builder, returning an empty [`DecayingBloomFilter`] with `num_generations` generations,
+    /// each sized for `expected_items` inserted between two ticks.
+    ///
+    /// Each generation gets `1 / num_generations` of the desired false positive rate, so that
+    /// [`contains`](DecayingBloomFilter::contains), which checks all generations, meets the desired rate.
+    ///
+    /// Note: `expected_items` will internally be set to 1 if 0 is specified.
+    ///
+    /// # Panics
+    /// Panics if `num_generations` is 0.
+    ///
+    /// # Examples
+    /// ```
+    /// use fastbloom::BloomFilter;
+    ///
+    /// let filter = BloomFilter::with_false_pos(0.001).decaying_expected_items(1000, 6);
+    /// assert_eq!(filter.num_generations(), 6);
+    /// ```
+    pub fn decaying_expected_items(
+        self,
+        expected_items: usize,
+        num_generations: usize,
+    ) -> DecayingBloomFilter<S> {
+        assert!(
+            num_generations > 0,
+            "num_generations must be greater than 0"
+        );
+        let builder = BuilderWithFalsePositiveRate {
+            desired_fp_rate: self.desired_fp_rate / num_generations as f64,
+            hasher: self.hasher,
+        }
+        .into_bits_builder(expected_items);
+        let num_hashes = builder.optimal_hashes(expected_items);
+        builder.decaying_hashes(num_hashes, num_generations)
+    }
+}
+
+impl<S: BuildHasher> DecayingBloomFilter<S> {
+    /// Inserts an element into the current generation, so it survives the next `num_generations - 1` ticks.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
+    /// `false` otherwise.
+    #[inline]
+    pub fn insert(&mut self, val: &(impl Hash + ?Sized)) -> bool {
+        self.insert_at(val, self.generation)
+    }
+
+    /// Inserts an element into `generation`, as if it had been inserted before the ticks since then.
+    ///
+    /// The item expires with `generation`, and is found by [`contains_since`](Self::contains_since)
+    /// only for a `min_generation` of at most `generation`.
+    /// Items of expired generations are not inserted.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
+    /// `false` otherwise.
+    ///
+    /// # Panics
+    /// Panics if `generation` is newer than the [current generation](Self::generation).
+    ///
+    /// # Examples
+    /// ```
+    /// use fastbloom::BloomFilter;
+    ///
+    /// let mut filter = BloomFilter::with_num_bits(1024).decaying_hashes(4, 3);
+    /// filter.tick();
+    /// filter.tick();
+    /// filter.insert_at(&1, 1);
+    /// filter.tick();
+    /// assert!(filter.contains(&1));
+    /// filter.tick();
+    /// assert!(!filter.contains(&1));
+    /// ```
+    #[inline]
+    pub fn insert_at(&mut self, val: &(impl Hash + ?Sized), generation: u64) -> bool {
+        assert!(
+            generation <= self.generation,
+            "generation must not be newer than the current generation"
+        );
+        let hash = self.generations[0].source_hash(val);
+        let previously_contained = self.contains_hash(hash, 0);
+        if !self.is_expired(generation) {
+            let index = self.index(generation);
+            self.generations[index].insert_hash(hash);
+        }
+        previously_contained
+    }
+
+    /// Checks if an element is possibly in any generation, i.e. was possibly inserted and has not expired.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
+    #[inline]
+    pub fn contains(&self, val: &(impl Hash + ?Sized)) -> bool {
+        self.contains_since(val, 0)
+    }
+
+    /// Checks if an element is possibly in a generation no older than `min_generation`,
+    /// i.e. was possibly inserted since then and has not expired.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
+    ///
+    /// # Examples
+    /// ```
+    /// use fastbloom::BloomFilter;
+    ///
+    /// let mut filter = BloomFilter::with_num_bits(1024).decaying_hashes(4, 3);
+    /// filter.insert(&1);
+    /// filter.tick();
+    /// assert!(filter.contains_since(&1, 0));
+    /// assert!(!filter.contains_since(&1, filter.generation()));
+    /// ```
+    #[inline]
+    pub fn contains_since(&self, val: &(impl Hash + ?Sized), min_generation: u64) -> bool {
+        self.contains_hash(self.generations[0].source_hash(val), min_generation)
+    }
+
+    #[inline]
+    fn contains_hash(&self, hash: u64, min_generation: u64) -> bool {
+        let oldest = (self.generation + 1).saturating_sub(self.generations.len() as u64);
+        (min_generation.max(oldest)..=self.generation)
+            .any(|generation| self.generations[self.index(generation)].contains_hash(hash))
+    }
+
+    /// Returns the index in `generations` of `generation`.
+    #[inline]
+    fn index(&self, generation: u64) -> usize {
+        (generation % self.generations.len() as u64) as usize
+    }
+
+    #[inline]
+    fn is_expired(&self, generation: u64) -> bool {
+        self.generation - generation >= self.generations.len() as u64
+    }
+
+    /// Advances time by one tick: the oldest generation is cleared, expiring its items, and becomes the current one.
+    #[inline]
+    pub fn tick(&mut self) {
+        self.generation += 1;
+        let index = self.index(self.generation);
+        self.generations[index].clear();
+    }
+
+    /// Returns the current generation, i.e. the number of ticks so far.
+    /// New items are inserted into this generation.
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the number of generations.
+    #[inline]
+    pub fn num_generations(&self) -> usize {
+        self.generations.len()
+    }
+
+    /// Returns the number of hashes per item.
+    #[inline]
+    pub fn num_hashes(&self) -> u32 {
+        self.generations[0].num_hashes()
+    }
+
+    /// Returns the total number of in-memory bits of all generations.
+    #[inline]
+    pub fn num_bits(&self) -> usize {
+        self.generations.iter().map(|g| g.num_bits()).sum()
+    }
+
+    /// Returns the fraction of bits set in the current generation.
+    ///
+    /// This grows with the items inserted since the last tick, see [`RotationPolicy`].
+    #[inline]
+    pub fn density(&self) -> f64 {
+        self.generations[self.index(self.generation)].density()
+    }
+
+    /// Ticks if `policy` says so, see [`RotationPolicy::should_rotate`].
+    ///
+    /// # Returns
+    ///
+    /// `true` if the filter ticked, `false` otherwise.
+    ///
+    /// # Examples
+    /// ```
+    /// use fastbloom::{BloomFilter, RotationPolicy};
+    ///
+    /// let policy = RotationPolicy::max_density(0.5);
+    /// let mut filter = BloomFilter::with_num_bits(1024).decaying_hashes(4, 2);
+    /// for x in 0..1000 {
+    ///     filter.insert(&x);
+    ///     filter.tick_if(&policy);
+    /// }
+    /// assert!(filter.density() < 0.5);
+    /// assert!(filter.generation() > 0);
+    /// ```
+    #[inline]
+    pub fn tick_if(&mut self, policy: &RotationPolicy) -> bool {
+        let should_rotate = policy.should_rotate(self);
+        if should_rotate {
+            self.tick();
+        }
+        should_rotate
+    }
+
+    /// Clear all generations, removing all items.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.generations.iter_mut().for_each(|g| g.clear());
+    }
+}
+
+/// Decides when a [`DecayingBloomFilter`] should tick, based on how full its current generation is.
+///
+/// Ticking by fill level instead of on a timer bounds the false positive rate regardless of the insert rate,
+/// at the cost of items expiring sooner when inserts are frequent.
+/// Pass a policy to [`DecayingBloomFilter::tick_if`] after inserts, or check [`should_rotate`](Self::should_rotate)
+/// to combine it with other conditions.
+///
+/// # Examples
+/// ```
+/// use fastbloom::{BloomFilter, RotationPolicy};
+///
+/// let policy = RotationPolicy::max_density(0.5);
+/// let mut filter = BloomFilter::with_num_bits(1024).decaying_hashes(4, 2);
+/// assert!(!policy.should_rotate(&filter));
+/// filter.extend(0..1000);
+/// assert!(policy.should_rotate(&filter));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RotationPolicy {
+    max_density: f64,
+}
+
+impl RotationPolicy {
+    /// Returns a policy that rotates once the [density](DecayingBloomFilter::density) of the current generation
+    /// reaches `max_density`.
+    ///
+    /// A generation with `k` hashes and density `d` has a false positive rate of about `d^k`,
+    /// so use `max_density = fp^(1/k)` to rotate before a generation exceeds `fp`.
+    ///
+    /// # Panics
+    /// Panics if `max_density` is not in `(0, 1]`.
+    pub fn max_density(max_density: f64) -> Self {
+        assert!(
+            max_density > 0.0 && max_density <= 1.0,
+            "max_density must be in (0, 1]"
+        );
+        Self { max_density }
+    }
+
+    /// Returns `true` if `filter` should tick, i.e. the density of its current generation has reached the maximum.
+    #[inline]
+    pub fn should_rotate<S: BuildHasher>(&self, filter: &DecayingBloomFilter<S>) -> bool {
+        filter.density() >= self.max_density
+    }
+}
+
+impl<T: Hash, S: BuildHasher> Extend<T> for DecayingBloomFilter<S> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.insert(&val);
+        }
+    }
+}
+
+#[cfg(not(feature = "loom"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(num_generations: usize) -> DecayingBloomFilter {
+        BloomFilter::with_false_pos(0.001)
+            .seed(&1)
+            .decaying_expected_items(1000, num_generations)
+    }
+
+    #[test]
+    fn items_expire() {
+        for num_generations in [1, 2, 5] {
+            let mut filter = filter(num_generations);
+            filter.extend(0..1000u64);
+            for _ in 0..num_generations - 1 {
+                filter.tick();
+                assert!((0..1000u64).all(|x| filter.contains(&x)));
+            }
+            filter.tick();
+            let num_remaining = (0..1000u64).filter(|x| filter.contains(x)).count();
+            assert_eq!(num_remaining, 0);
+        }
+    }
+
+    #[test]
+    fn insert_refreshes() {
+        let mut filter = filter(3);
+        assert!(!filter.insert(&1));
+        for _ in 0..10 {
+            filter.tick();
+            assert!(filter.insert(&1));
+        }
+        filter.tick();
+        filter.tick();
+        assert!(filter.contains(&1));
+        filter.tick();
+        assert!(!filter.contains(&1));
+    }
+
+    #[test]
+    fn generations_share_hasher() {
+        let mut filter = filter(4);
+        for t in 0..4u64 {
+            filter.extend(t * 1000..(t + 1) * 1000);
+            filter.tick();
+        }
+        assert_eq!(filter.num_generations(), 4);
+        assert_eq!(filter.num_bits(), 4 * filter.generations[0].num_bits());
+        assert!((1000..4000u64).all(|x| filter.contains(&x)));
+        filter.clear();
+        assert!((0..4000u64).all(|x| !filter.contains(&x)));
+    }
+
+    #[test]
+    fn meets_fp() {
+        let fp = 0.01;
+        let mut filter = BloomFilter::with_false_pos(fp)
+            .seed(&1)
+            .decaying_expected_items(1000, 4);
+        for t in 0..4u64 {
+            filter.extend(t * 1000..(t + 1) * 1000);
+            filter.tick();
+        }
+        let trials = 100_000;
+        let false_positives = (4000..4000 + trials).filter(|x| filter.contains(x)).count();
+        let sample_fp = false_positives as f64 / trials as f64;
+        assert!(sample_fp < fp, "sample fp: {sample_fp}, target: {fp}");
+    }
+
+    #[test]
+    fn builder_bits_expire() {
+        let bits = BloomFilter::with_num_bits(1024).seed(&1).items([1].iter());
+        let mut filter = BloomFilter::from_vec(bits.as_slice().to_vec())
+            .seed(&1)
+            .decaying_hashes(bits.num_hashes(), 2);
+        assert!(filter.contains(&1));
+        filter.tick();
+        assert!(filter.contains(&1));
+        filter.tick();
+        assert!(!filter.contains(&1));
+    }
+
+    #[test]
+    fn contains_since() {
+        let mut filter = filter(4);
+        for t in 0..4u64 {
+            filter.extend(t * 1000..(t + 1) * 1000);
+            filter.tick();
+        }
+        assert_eq!(filter.generation(), 4);
+        assert!((1000..4000u64).all(|x| filter.contains_since(&x, 1)));
+        let num_old = (1000..2000u64)
+            .filter(|x| filter.contains_since(x, 2))
+            .count();
+        assert!(num_old < 10, "{num_old} items of generation 1 reported");
+        assert!((2000..4000u64).all(|x| filter.contains_since(&x, 2)));
+        assert!((0..4000u64).all(|x| !filter.contains_since(&x, 5)));
+    }
+
+    #[test]
+    fn insert_at() {
+        let mut filter = filter(3);
+        for _ in 0..5 {
+            filter.tick();
+        }
+        assert!(!filter.insert_at(&1, 3));
+        assert!(!filter.insert_at(&2, 2));
+        assert!(filter.contains_since(&1, 3));
+        assert!(!filter.contains_since(&1, 4));
+        assert!(!filter.contains(&2));
+        filter.tick();
+        assert!(!filter.contains(&1));
+    }
+
+    #[test]
+    fn rotation_policy() {
+        let policy = RotationPolicy::max_density(0.25);
+        let mut filter = filter(2);
+        let num_bits = filter.generations[0].num_bits() as f64;
+        let mut x = 0u64;
+        while filter.density() < 0.25 {
+            assert!(!policy.should_rotate(&filter));
+            assert!(!filter.tick_if(&policy));
+            filter.insert(&x);
+            x += 1;
+        }
+        assert!(filter.density() < 0.25 + filter.num_hashes() as f64 / num_bits);
+        assert!(policy.should_rotate(&filter));
+        assert!(filter.tick_if(&policy));
+        assert_eq!(filter.generation(), 1);
+        assert_eq!(filter.density(), 0.0);
+        assert!(!policy.should_rotate(&filter));
+        assert!((0..x).all(|x| filter.contains(&x)));
+    }
+
+    #[test]
+    #[should_panic(expected = "max_density must be in (0, 1]")]
+    fn rotation_policy_zero_density() {
+        let _ = RotationPolicy::max_density(0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "generation must not be newer than the current generation")]
+    fn insert_at_future() {
+        filter(3).insert_at(&1, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "num_generations must be greater than 0")]
+    fn zero_generations() {
+        let _ = filter(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "num_generations must be greater than 0")]
+    fn zero_generations_bits() {
+        let _ = BloomFilter::with_num_bits(1024).decaying_hashes(4, 0);
+    }
+}
@@ -0,0 +1,288 @@
+use crate::{index, BuildHasher, DefaultHasher, DoubleHasher};
+use core::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+};
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for u32 {}
+    impl Sealed for u64 {}
+}
+
+/// An unsigned integer used as the storage word of a [`FixedBloomFilter`]: `u32` or `u64`.
+///
+/// `u32` words avoid emulated 64-bit operations on 32-bit targets. The choice of word doesn't change which bits are
+/// probed, so Bloom filters with the same total number of bits answer membership queries identically.
+pub trait BitWord: Copy + Default + Eq + Debug + private::Sealed {
+    /// The number of bits in the word.
+    const BITS: usize;
+
+    /// Returns `true` if bit `bit` is set.
+    fn check(self, bit: usize) -> bool;
+
+    /// Sets bit `bit`, returning `true` if it was previously set.
+    fn set(&mut self, bit: usize) -> bool;
+}
+
+macro_rules! impl_bit_word {
+    ($ty:ty) => {
+        impl BitWord for $ty {
+            const BITS: usize = <$ty>::BITS as usize;
+
+            #[inline]
+            fn check(self, bit: usize) -> bool {
+                self & (1 << bit) != 0
+            }
+
+            #[inline]
+            fn set(&mut self, bit: usize) -> bool {
+                let previously_set = self.check(bit);
+                *self |= 1 << bit;
+                previously_set
+            }
+        }
+    };
+}
+
+impl_bit_word!(u32);
+impl_bit_word!(u64);
+
+/// A Bloom filter with a size fixed at compile time, stored inline in a `[W; WORDS]` instead of on the heap.
+///
+/// [`FixedBloomFilter`] has `WORDS * W::BITS` bits and never allocates, so it can live on the stack or in a `static`
+/// on targets without an allocator. It probes exactly like a `BloomFilter` with the same
+/// number of bits, number of hashes and hasher, so both answer membership queries identically.
+/// It is the only Bloom filter available without the `alloc` feature.
+///
+/// # Examples
+/// ```
+/// use fastbloom::FixedBloomFilter;
+///
+/// let mut filter = FixedBloomFilter::<16>::hashes(4);
+/// filter.insert(&42);
+/// assert!(filter.contains(&42));
+/// assert_eq!(filter.num_bits(), 1024);
+/// ```
+/// The storage word defaults to `u64`, and can be set to `u32` (see [`BitWord`]):
+/// ```
+/// use fastbloom::{DefaultHasher, FixedBloomFilter};
+///
+/// let mut filter = FixedBloomFilter::<32, DefaultHasher, u32>::hashes(4);
+/// filter.insert(&42);
+/// assert!(filter.contains(&42));
+/// assert_eq!(filter.num_bits(), 1024);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FixedBloomFilter<const WORDS: usize, S = DefaultHasher, W: BitWord = u64> {
+    bits: [W; WORDS],
+    num_hashes: u32,
+    hasher: S,
+}
+
+impl<const WORDS: usize, W: BitWord> FixedBloomFilter<WORDS, DefaultHasher, W> {
+    /// Creates an empty [`FixedBloomFilter`] using `num_hashes` hashes per item and a random [`DefaultHasher`].
+    ///
+    /// # Panics
+    /// Panics if `WORDS` is 0.
+    pub fn hashes(num_hashes: u32) -> Self {
+        Self::with_hasher(num_hashes, DefaultHasher::default())
+    }
+
+    /// Sets the seed for this Bloom filter's [`DefaultHasher`]. Only do this before inserting any items.
+    ///
+    /// # Examples
+    /// ```
+    /// use fastbloom::FixedBloomFilter;
+    ///
+    /// let filter = FixedBloomFilter::<16>::hashes(4).seed(&1);
+    /// ```
+    pub fn seed(mut self, seed: &u128) -> Self {
+        self.hasher = DefaultHasher::seeded(&seed.to_be_bytes());
+        self
+    }
+}
+
+impl<const WORDS: usize, S: BuildHasher, W: BitWord> FixedBloomFilter<WORDS, S, W> {
+    /// Creates an empty [`FixedBloomFilter`] using `num_hashes` hashes per item and `hasher`.
+    /// At least one hash is always used.
+    ///
+    /// # Panics
+    /// Panics if `WORDS` is 0.
+    pub fn with_hasher(num_hashes: u32, hasher: S) -> Self {
+        assert!(WORDS > 0, "WORDS must be greater than 0");
+        Self {
+            bits: [W::default(); WORDS],
+            num_hashes: num_hashes.max(1),
+            hasher,
+        }
+    }
+
+    /// Inserts an element into the Bloom filter.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
+    /// `false` otherwise.
+    #[inline]
+    pub fn insert(&mut self, val: &(impl Hash + ?Sized)) -> bool {
+        self.insert_hash(self.source_hash(val))
+    }
+
+    /// Inserts the hash of an element into the Bloom filter.
+    /// That is the element is pre-hashed and all subsequent hashes are derived from this "source" hash.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
+    /// `false` otherwise.
+    #[inline]
+    pub fn insert_hash(&mut self, hash: u64) -> bool {
+        let mut previously_contained = true;
+        for i in self.indices(hash) {
+            previously_contained &= self.bits[i / W::BITS].set(i % W::BITS);
+        }
+        previously_contained
+    }
+
+    /// Checks if an element is possibly in the Bloom filter.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
+    #[inline]
+    pub fn contains(&self, val: &(impl Hash + ?Sized)) -> bool {
+        self.contains_hash(self.source_hash(val))
+    }
+
+    /// Checks if the hash of an element is possibly in the Bloom filter.
+    /// That is the element is pre-hashed and all subsequent hashes are derived from this "source" hash.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
+    #[inline]
+    pub fn contains_hash(&self, hash: u64) -> bool {
+        self.indices(hash)
+            .all(|i| self.bits[i / W::BITS].check(i % W::BITS))
+    }
+
+    /// Returns the number of hashes per item.
+    #[inline]
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    /// Returns the number of bits, `WORDS * W::BITS`.
+    #[inline]
+    pub fn num_bits(&self) -> usize {
+        WORDS * W::BITS
+    }
+
+    /// Returns the underlying slice of this Bloom filter's bit contents.
+    #[inline]
+    pub fn as_slice(&self) -> &[W] {
+        &self.bits
+    }
+
+    /// Clear all of the bits in the Bloom filter, removing all items.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.bits = [W::default(); WORDS];
+    }
+
+    /// Returns the hash of `val` using this Bloom filter's hasher.
+    /// The resulting value can be used in [`Self::insert_hash`] or [`Self::contains_hash`].
+    #[inline]
+    pub fn source_hash(&self, val: &(impl Hash + ?Sized)) -> u64 {
+        let mut state = self.hasher.build_hasher();
+        val.hash(&mut state);
+        state.finish()
+    }
+
+    /// Returns the bit index of each of the item's hashes, probing like [`BloomFilter`](crate::BloomFilter) does.
+    #[inline]
+    fn indices(&self, hash: u64) -> impl Iterator<Item = usize> {
+        let num_bits = self.num_bits();
+        let mut hasher = DoubleHasher::new(hash);
+        core::iter::once(index(num_bits, hash))
+            .chain((1..self.num_hashes).map(move |_| index(num_bits, hasher.next())))
+    }
+}
+
+impl<T: Hash, const WORDS: usize, S: BuildHasher, W: BitWord> Extend<T>
+    for FixedBloomFilter<WORDS, S, W>
+{
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.insert(&val);
+        }
+    }
+}
+
+#[cfg(not(feature = "loom"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn matches_bloom_filter() {
+        use crate::BloomFilter;
+
+        for num_hashes in [1, 2, 4, 7] {
+            let mut fixed = FixedBloomFilter::<32>::hashes(num_hashes).seed(&1);
+            let mut bloom = BloomFilter::with_num_bits(32 * 64)
+                .seed(&1)
+                .hashes(num_hashes);
+            for x in 0..200u64 {
+                assert_eq!(fixed.insert(&x), bloom.insert(&x));
+            }
+            assert_eq!(fixed.as_slice(), bloom.as_slice());
+            for x in 0..10_000u64 {
+                assert_eq!(fixed.contains(&x), bloom.contains(&x));
+            }
+        }
+    }
+
+    #[test]
+    fn u32_words_match_u64_words() {
+        for num_hashes in [1, 3, 8] {
+            let mut wide = FixedBloomFilter::<8>::hashes(num_hashes).seed(&1);
+            let mut narrow =
+                FixedBloomFilter::<16, DefaultHasher, u32>::hashes(num_hashes).seed(&1);
+            assert_eq!(wide.num_bits(), narrow.num_bits());
+            for x in 0..50u64 {
+                assert_eq!(wide.insert(&x), narrow.insert(&x));
+            }
+            for x in 0..10_000u64 {
+                assert_eq!(wide.contains(&x), narrow.contains(&x));
+            }
+            for (i, word) in wide.as_slice().iter().enumerate() {
+                assert_eq!(*word as u32, narrow.as_slice()[2 * i]);
+                assert_eq!((*word >> 32) as u32, narrow.as_slice()[2 * i + 1]);
+            }
+        }
+    }
+
+    #[test]
+    fn clear() {
+        let mut filter = FixedBloomFilter::<4>::hashes(3);
+        filter.extend(0..10u64);
+        assert!((0..10u64).all(|x| filter.contains(&x)));
+        filter.clear();
+        assert!(filter.as_slice().iter().all(|&x| x == 0));
+    }
+
+    #[test]
+    fn at_least_one_hash() {
+        assert_eq!(FixedBloomFilter::<1>::hashes(0).num_hashes(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "WORDS must be greater than 0")]
+    fn zero_words() {
+        let _ = FixedBloomFilter::<0>::hashes(4);
+    }
+}
@@ -34,6 +34,31 @@ impl DefaultHasher {
             hasher: RandomDefaultHasher::seeded(seed),
         }
     }
+
+    /// Returns a hasher keyed by the 128-bit SipHash-1-3 hash of `bytes`, so seeds of any length can be used.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn seeded_from_bytes(bytes: &[u8]) -> Self {
+        use siphasher::sip128::{Hasher128, SipHasher13 as SipHasher13_128};
+        let mut hasher = SipHasher13_128::new();
+        hasher.write(bytes);
+        Self::seeded(&hasher.finish128().as_bytes())
+    }
+
+    /// Returns the SipHash keys, which fully determine the hashes.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub(crate) fn keys(&self) -> (u64, u64) {
+        self.hasher.0.keys()
+    }
+
+    /// Inverse of [`keys`](Self::keys).
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub(crate) fn with_keys(key0: u64, key1: u64) -> Self {
+        Self {
+            hasher: RandomDefaultHasher(SipHasher13::new_with_keys(key0, key1)),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -47,10 +72,26 @@ impl RandomDefaultHasher {
     }
 }
 
+// Without `rand`, the seed comes from `foldhash`, whose only entropy on `wasm32-unknown-unknown` is memory addresses,
+// which are the same on every run. Refuse to build rather than silently hand out predictable seeds.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown", not(feature = "rand")))]
+compile_error!(
+    "fastbloom has no entropy source on wasm32-unknown-unknown without the `rand` feature. \
+     Enable `rand` and `getrandom`'s `wasm_js` backend."
+);
+
+/// The key used by [`RandomDefaultHasher::default`] with the `fixed-seed` feature.
+#[cfg(feature = "fixed-seed")]
+const FIXED_SEED: [u8; 16] = *b"fastbloom-fixed!";
+
 impl Default for RandomDefaultHasher {
     #[inline]
     fn default() -> Self {
-        #[cfg(not(feature = "rand"))]
+        #[cfg(feature = "fixed-seed")]
+        {
+            Self::seeded(&FIXED_SEED)
+        }
+        #[cfg(all(not(feature = "fixed-seed"), not(feature = "rand")))]
         {
             use foldhash::fast::RandomState;
 
@@ -62,9 +103,9 @@ impl Default for RandomDefaultHasher {
             let low = state_a.build_hasher().finish() as u128;
             let high = state_b.build_hasher().finish() as u128;
 
-            Self::seeded(&((high << 64) | low).to_ne_bytes())
+            Self::seeded(&((high << 64) | low).to_le_bytes())
         }
-        #[cfg(feature = "rand")]
+        #[cfg(all(not(feature = "fixed-seed"), feature = "rand"))]
         {
             let mut seed = [0u8; 16];
             use rand::RngCore;
@@ -133,10 +174,12 @@ impl Hasher for RandomDefaultHasher {
     }
 }
 
+#[cfg(feature = "alloc")]
 #[cfg(test)]
 mod test {
-    use crate::hasher::RandomDefaultHasher;
-    use core::hash::Hasher;
+    use crate::{hasher::RandomDefaultHasher, BloomFilter, DoubleHasher};
+    use alloc::vec::Vec;
+    use core::hash::{Hash, Hasher};
     use siphasher::sip::SipHasher13;
 
     fn hash_all(mut x: impl Hasher) -> u64 {
@@ -163,6 +206,57 @@ mod test {
         assert_eq!(hash_all(h1), hash_all(h2),);
     }
 
+    #[test]
+    fn test_seed_to_key() {
+        let seed = 0x0001_0203_0405_0607_0809_0a0b_0c0d_0e0fu128;
+        let key = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+        let bloom = BloomFilter::with_num_bits(64).seed(&seed).hashes(1);
+        let mut h = SipHasher13::new_with_key(&key);
+        42u64.hash(&mut h);
+        assert_eq!(bloom.source_hash(&42u64), h.finish());
+        assert_eq!(bloom.source_hash(&42u64), 0x2d9b_012a_8072_94cb);
+    }
+
+    #[test]
+    fn test_double_hasher_sequence() {
+        let mut hasher = DoubleHasher::new(0x2d9b_012a_8072_94cb);
+        let hashes: Vec<u64> = (0..4).map(|_| hasher.next()).collect();
+        assert_eq!(
+            hashes,
+            [
+                0x6f43_6810_fac9_218c,
+                0xa450_44e0_459a_b9b4,
+                0x45eb_dec9_9fcd_bebb,
+                0x795f_1bf4_e62e_5f8f
+            ]
+        );
+    }
+
+    #[cfg(feature = "fixed-seed")]
+    #[test]
+    fn test_fixed_seed_default_hasher() {
+        let h1 = RandomDefaultHasher::default();
+        let h2 = RandomDefaultHasher::default();
+        assert_eq!(h1.finish(), h2.finish());
+
+        let a = BloomFilter::with_num_bits(1024).items([1, 2, 3].iter());
+        let b = BloomFilter::with_num_bits(1024).items([1, 2, 3].iter());
+        assert_eq!(a.source_hash(&42), b.source_hash(&42));
+        assert_eq!(a.as_slice(), b.as_slice());
+    }
+
+    #[cfg(all(target_arch = "wasm32", not(feature = "fixed-seed")))]
+    #[test]
+    fn test_wasm_default_hashers_differ() {
+        let hashes: Vec<u64> = (0..16)
+            .map(|_| RandomDefaultHasher::default().finish())
+            .collect();
+        for (i, a) in hashes.iter().enumerate() {
+            assert!(hashes[i + 1..].iter().all(|b| a != b));
+        }
+    }
+
+    #[cfg(not(feature = "fixed-seed"))]
     #[test]
     fn test_random_default_hasher() {
         // two different instances of RandomDefaultHasher should have different seeds
@@ -182,8 +276,38 @@ mod test {
     }
 }
 
-#[derive(Clone, Copy)]
-pub(crate) struct DoubleHasher {
+/// The double hashing scheme used by all Bloom filters in this crate to derive an item's hashes from its source hash.
+///
+/// A Bloom filter with `num_hashes` hashes probes `num_hashes` of its `num_bits` bits for each item. The first bit
+/// comes from the item's source hash itself, and each further bit from a call to [`DoubleHasher::next`]. A hash `h` is
+/// mapped to the bit index `(h as u128 * num_bits as u128) >> 64`. Custom filters that follow these steps probe the
+/// same bits as `BloomFilter`.
+///
+/// The sequence of hashes produced for a given source hash is stable, and changing it is a breaking change.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use fastbloom::{BloomFilter, DoubleHasher};
+///
+/// let (num_bits, num_hashes) = (1024, 4);
+/// let mut bloom = BloomFilter::with_num_bits(num_bits).seed(&1).hashes(num_hashes);
+/// let index = |h: u64| ((h as u128 * num_bits as u128) >> 64) as usize;
+///
+/// let source_hash = bloom.source_hash(&42);
+/// let mut hasher = DoubleHasher::new(source_hash);
+/// let indices: Vec<usize> = std::iter::once(index(source_hash))
+///     .chain((1..num_hashes).map(|_| index(hasher.next())))
+///     .collect();
+///
+/// bloom.insert(&42);
+/// for i in indices {
+///     assert!(bloom.as_slice()[i / 64] & (1 << (i % 64)) != 0);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct DoubleHasher {
     h1: u64,
     h2: u64,
 }
@@ -191,11 +315,11 @@ pub(crate) struct DoubleHasher {
 impl DoubleHasher {
     /// The first two hashes of the value, h1 and h2.
     ///
-    /// Subsequent hashes, h, are efficiently derived from these two using `next_hash`.
+    /// Subsequent hashes, h, are efficiently derived from these two using [`Self::next`].
     ///
     /// This strategy is a modified version of <https://www.eecs.harvard.edu/~michaelm/postscripts/rsa2008.pdf>.
     #[inline]
-    pub(crate) fn new(h1: u64) -> Self {
+    pub fn new(h1: u64) -> Self {
         // 0xffff_ffff_ffff_ffff / 0x517c_c1b7_2722_0a95 = π
         let h2 = h1.wrapping_mul(0x51_7c_c1_b7_27_22_0a_95);
         Self { h1, h2 }
@@ -205,7 +329,8 @@ impl DoubleHasher {
     ///
     /// Modified from <https://www.eecs.harvard.edu/~michaelm/postscripts/rsa2008.pdf>.
     #[inline]
-    pub(crate) fn next(&mut self) -> u64 {
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> u64 {
         self.h1 = self.h1.rotate_left(5).wrapping_add(self.h2);
         self.h1
     }
@@ -0,0 +1,193 @@
+use crate::{
+    index, BloomFilter, BuildHasher, BuilderWithBits, BuilderWithFalsePositiveRate, DefaultHasher,
+};
+use core::hash::{Hash, Hasher};
+
+/// A Bloom filter that computes each of an item's hashes independently, instead of deriving them from one source hash.
+///
+/// A [`BloomFilter`] hashes an item once and derives the remaining hashes with [`DoubleHasher`](crate::DoubleHasher),
+/// which is fast but makes the hashes of an item correlated. With many hashes (e.g. more than 20) the correlation can
+/// push the false positive rate above the theoretical rate. [`IndependentBloomFilter`] instead hashes the item once per
+/// hash, each time salted with the hash's index, so the hashes are as independent as the hasher allows.
+/// This trades speed, one full hash per probed bit, for accuracy.
+///
+/// An [`IndependentBloomFilter`] is built with [`BloomFilter`]'s builders, ending in
+/// [`independent_expected_items`](BuilderWithFalsePositiveRate::independent_expected_items) or
+/// [`independent_hashes`](BuilderWithBits::independent_hashes).
+///
+/// # Examples
+/// ```
+/// use fastbloom::BloomFilter;
+///
+/// let mut filter = BloomFilter::with_num_bits(1 << 16).independent_hashes(24);
+/// filter.insert(&42);
+/// assert!(filter.contains(&42));
+/// ```
+#[derive(Debug, Clone)]
+pub struct IndependentBloomFilter<S = DefaultHasher> {
+    bloom: BloomFilter<S>,
+}
+
+impl<S: BuildHasher> BuilderWithBits<S> {
+    /// "Consumes" this builder, using the provided `num_hashes` to return an empty [`IndependentBloomFilter`].
+    ///
+    /// Note: if `num_hashes` is 0, it is treated as 1.
+    ///
+    /// # Examples
+    /// ```
+    /// use fastbloom::BloomFilter;
+    ///
+    /// let filter = BloomFilter::with_num_bits(1 << 16).seed(&1).independent_hashes(24);
+    /// assert_eq!(filter.num_hashes(), 24);
+    /// ```
+    pub fn independent_hashes(self, num_hashes: u32) -> IndependentBloomFilter<S> {
+        IndependentBloomFilter {
+            bloom: self.hashes(num_hashes),
+        }
+    }
+}
+
+impl<S: BuildHasher> BuilderWithFalsePositiveRate<S> {
+    /// "Consumes" this builder, using the provided `expected_items` to return an empty [`IndependentBloomFilter`]
+    /// with the number of bits and hashes [`expected_items`](Self::expected_items) would choose.
+    ///
+    /// Note: `expected_items` will internally be set to 1 if 0 is specified.
+    ///
+    /// # Examples
+    /// ```
+    /// use fastbloom::BloomFilter;
+    ///
+    /// let filter = BloomFilter::with_false_pos(0.001).independent_expected_items(500);
+    /// ```
+    pub fn independent_expected_items(self, expected_items: usize) -> IndependentBloomFilter<S> {
+        IndependentBloomFilter {
+            bloom: self.expected_items(expected_items),
+        }
+    }
+}
+
+impl<S: BuildHasher> IndependentBloomFilter<S> {
+    /// Inserts an element into the Bloom filter.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
+    /// `false` otherwise.
+    #[inline]
+    pub fn insert(&mut self, val: &(impl Hash + ?Sized)) -> bool {
+        let mut previously_contained = true;
+        for salt in 0..self.num_hashes() {
+            let i = self.index(val, salt);
+            previously_contained &= self.bloom.bits.set(i);
+        }
+        previously_contained
+    }
+
+    /// Checks if an element is possibly in the Bloom filter.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
+    #[inline]
+    pub fn contains(&self, val: &(impl Hash + ?Sized)) -> bool {
+        (0..self.num_hashes()).all(|salt| self.bloom.bits.check(self.index(val, salt)))
+    }
+
+    /// Returns the bit index of the item's hash salted with `salt`.
+    #[inline]
+    fn index(&self, val: &(impl Hash + ?Sized), salt: u32) -> usize {
+        let mut state = self.bloom.hasher.build_hasher();
+        state.write_u32(salt);
+        val.hash(&mut state);
+        index(self.num_bits(), state.finish())
+    }
+
+    /// Returns the number of hashes per item.
+    #[inline]
+    pub fn num_hashes(&self) -> u32 {
+        self.bloom.num_hashes()
+    }
+
+    /// Returns the total number of in-memory bits supporting the Bloom filter.
+    #[inline]
+    pub fn num_bits(&self) -> usize {
+        self.bloom.num_bits()
+    }
+
+    /// Returns the underlying slice of this Bloom filter's bit contents.
+    #[inline]
+    pub fn as_slice(&self) -> &[u64] {
+        self.bloom.as_slice()
+    }
+
+    /// Clear all of the bits in the Bloom filter, removing all items.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.bloom.clear();
+    }
+}
+
+impl<T: Hash, S: BuildHasher> Extend<T> for IndependentBloomFilter<S> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.insert(&val);
+        }
+    }
+}
+
+#[cfg(not(feature = "loom"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_false_negatives() {
+        let mut filter = BloomFilter::with_num_bits(1 << 14).independent_hashes(6);
+        filter.extend(0..2000u64);
+        assert!((0..2000u64).all(|x| filter.contains(&x)));
+        filter.clear();
+        assert!(filter.as_slice().iter().all(|&x| x == 0));
+    }
+
+    #[test]
+    fn sized_like_bloom_filter() {
+        let bloom = BloomFilter::with_false_pos(0.001).expected_items(1000);
+        let filter = BloomFilter::with_false_pos(0.001).independent_expected_items(1000);
+        assert_eq!(filter.num_bits(), bloom.num_bits());
+        assert_eq!(filter.num_hashes(), bloom.num_hashes());
+    }
+
+    #[test]
+    fn many_hashes_fp() {
+        for (num_bits, num_hashes, num_items) in [(1 << 16, 24, 5000), (1 << 18, 32, 16000)] {
+            let mut double = BloomFilter::with_num_bits(num_bits)
+                .seed(&1)
+                .hashes(num_hashes);
+            let expected = double.expected_false_pos(num_items);
+            let mut independent = BloomFilter::with_num_bits(num_bits)
+                .seed(&1)
+                .independent_hashes(num_hashes);
+            double.extend(0..num_items as u64);
+            independent.extend(0..num_items as u64);
+
+            let trials = 100_000;
+            let sample_fp = |contains: &dyn Fn(&u64) -> bool| {
+                let false_positives = (num_items as u64..(num_items + trials) as u64)
+                    .filter(|x| contains(x))
+                    .count();
+                false_positives as f64 / trials as f64
+            };
+            for (mode, fp) in [
+                ("double", sample_fp(&|x| double.contains(x))),
+                ("independent", sample_fp(&|x| independent.contains(x))),
+            ] {
+                let err = (fp - expected).abs() / expected;
+                assert!(
+                    err < 0.15,
+                    "{mode} hashing, {num_hashes} hashes: sample fp: {fp}, expected: {expected}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{
+    BloomFilter, BuildHasher, BuilderWithBits, BuilderWithFalsePositiveRate, DefaultHasher,
+};
+use core::hash::Hasher;
+
+/// A Bloom filter specialized for integer keys.
+///
+/// Keys are mixed with an inlined avalanche function instead of going through [`Hash`](core::hash::Hash)
+/// and the filter's hasher, making [`insert`](Self::insert) and [`contains`](Self::contains) as cheap as possible.
+/// The underlying [`BloomFilter`] is only used for its bits and number of hashes; its hasher is
+/// used once to derive a per-filter key so different filters probe different bits.
+///
+/// An [`IntBloomFilter`] is built with [`BloomFilter`]'s builders, ending in
+/// [`int_expected_items`](BuilderWithFalsePositiveRate::int_expected_items) or
+/// [`int_hashes`](BuilderWithBits::int_hashes).
+///
+/// # Examples
+/// ```
+/// use fastbloom::BloomFilter;
+///
+/// let mut filter = BloomFilter::with_false_pos(0.001).int_expected_items(1000);
+/// filter.insert(42);
+/// filter.insert(u64::from(7u32));
+/// assert!(filter.contains(42));
+/// assert!(filter.contains(7));
+/// ```
+#[derive(Debug, Clone)]
+pub struct IntBloomFilter<S = DefaultHasher> {
+    bloom: BloomFilter<S>,
+    key: u64,
+}
+
+impl<S: BuildHasher> BuilderWithBits<S> {
+    /// "Consumes" this builder, using the provided `num_hashes` to return an empty [`IntBloomFilter`].
+    ///
+    /// Note: if `num_hashes` is 0, it is treated as 1.
+    ///
+    /// # Examples
+    /// ```
+    /// use fastbloom::BloomFilter;
+    ///
+    /// let filter = BloomFilter::with_num_bits(4096).seed(&1).int_hashes(4);
+    /// ```
+    pub fn int_hashes(self, num_hashes: u32) -> IntBloomFilter<S> {
+        IntBloomFilter::new(self.hashes(num_hashes))
+    }
+}
+
+impl<S: BuildHasher> BuilderWithFalsePositiveRate<S> {
+    /// "Consumes" this builder, using the provided `expected_items` to return an empty [`IntBloomFilter`]
+    /// with the number of bits and hashes [`expected_items`](Self::expected_items) would choose.
+    ///
+    /// Note: `expected_items` will internally be set to 1 if 0 is specified.
+    ///
+    /// # Examples
+    /// ```
+    /// use fastbloom::BloomFilter;
+    ///
+    /// let filter = BloomFilter::with_false_pos(0.001).int_expected_items(500);
+    /// ```
+    pub fn int_expected_items(self, expected_items: usize) -> IntBloomFilter<S> {
+        IntBloomFilter::new(self.expected_items(expected_items))
+    }
+}
+
+impl<S: BuildHasher> IntBloomFilter<S> {
+    /// Wraps the empty `bloom`, deriving the key from its hasher.
+    fn new(bloom: BloomFilter<S>) -> Self {
+        let mut state = bloom.hasher.build_hasher();
+        state.write_u64(0);
+        let key = state.finish();
+        Self { bloom, key }
+    }
+
+    /// Inserts an integer into the Bloom filter.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the integer may have been previously in the Bloom filter (indicating a potential false positive),
+    /// `false` otherwise.
+    #[inline]
+    pub fn insert(&mut self, key: u64) -> bool {
+        self.bloom.insert_hash(mix(key ^ self.key))
+    }
+
+    /// Checks if an integer is possibly in the Bloom filter.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the integer is possibly in the Bloom filter, `false` otherwise.
+    #[inline]
+    pub fn contains(&self, key: u64) -> bool {
+        self.bloom.contains_hash(mix(key ^ self.key))
+    }
+
+    /// Returns the underlying [`BloomFilter`].
+    #[inline]
+    pub fn as_bloom(&self) -> &BloomFilter<S> {
+        &self.bloom
+    }
+
+    /// Consumes `self`, returning the underlying [`BloomFilter`].
+    #[inline]
+    pub fn into_bloom(self) -> BloomFilter<S> {
+        self.bloom
+    }
+}
+
+/// The 64-bit finalizer from MurmurHash3. It is a bijection, so distinct keys never collide.
+#[inline(always)]
+fn mix(mut x: u64) -> u64 {
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    x ^= x >> 33;
+    x
+}
+
+#[cfg(not(feature = "loom"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_target_fp() {
+        let fp = 0.01;
+        let num_items = 10_000;
+        let mut filter = BloomFilter::with_false_pos(fp)
+            .seed(&1)
+            .int_expected_items(num_items);
+        for x in 0..num_items as u64 {
+            filter.insert(x);
+        }
+        assert!((0..num_items as u64).all(|x| filter.contains(x)));
+
+        let trials = 100_000;
+        let false_positives = (num_items as u64..(num_items + trials) as u64)
+            .filter(|x| filter.contains(*x))
+            .count();
+        let sample_fp = false_positives as f64 / trials as f64;
+        assert!(sample_fp < fp * 1.5, "sample fp: {sample_fp}");
+    }
+
+    #[test]
+    fn seed_changes_bits() {
+        let build = |seed| {
+            let mut filter = BloomFilter::with_num_bits(4096).seed(&seed).int_hashes(4);
+            filter.insert(42);
+            filter.into_bloom()
+        };
+        assert_eq!(build(1), build(1));
+        assert!(build(1) != build(2));
+    }
+}
@@ -1,33 +1,97 @@
 #![allow(rustdoc::bare_urls)]
 #![warn(unreachable_pub)]
-#![doc = include_str!("../README.md")]
+// The README's examples use heap-allocated Bloom filters, so only test them with `alloc`.
+#![cfg_attr(any(not(doctest), feature = "alloc"), doc = include_str!("../README.md"))]
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
-use core::hash::{BuildHasher, Hash, Hasher};
-use core::iter::repeat;
+use core::hash::BuildHasher;
+#[cfg(feature = "alloc")]
+use core::hash::{Hash, Hasher};
+#[cfg(feature = "alloc")]
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign};
+#[cfg(feature = "alloc")]
+use core::sync::atomic::Ordering;
 mod hasher;
-pub use hasher::DefaultHasher;
-use hasher::DoubleHasher;
+pub use hasher::{DefaultHasher, DoubleHasher};
+#[cfg(feature = "alloc")]
 mod builder;
+#[cfg(feature = "alloc")]
+use builder::BuilderData;
+#[cfg(feature = "alloc")]
 pub use builder::{
-    expected_density, expected_false_pos, optimal_hashes, optimal_size, AtomicBuilderWithBits,
-    AtomicBuilderWithFalsePositiveRate, BuilderWithBits, BuilderWithFalsePositiveRate,
+    from_items, AtomicBuilderWithBits, AtomicBuilderWithFalsePositiveRate, BuildError,
+    BuilderWithBits, BuilderWithFalsePositiveRate,
 };
+#[cfg(feature = "alloc")]
 mod bit_vector;
+#[cfg(feature = "alloc")]
 use bit_vector::{AtomicBitVec, BitVec};
-mod math;
+#[cfg(feature = "alloc")]
+mod blocked;
+#[cfg(feature = "alloc")]
+pub use blocked::BlockedBloomFilter;
+#[cfg(feature = "alloc")]
+mod borrowed;
+#[cfg(feature = "alloc")]
+pub use borrowed::BloomFilterRef;
+#[cfg(feature = "alloc")]
+mod bytes;
+#[cfg(feature = "alloc")]
+pub use bytes::FromBytesError;
+#[cfg(feature = "alloc")]
+mod cached;
+#[cfg(feature = "alloc")]
+pub use cached::CachedBloomFilter;
+#[cfg(feature = "alloc")]
+mod counting;
+#[cfg(feature = "alloc")]
+pub use counting::{AtomicCountingBloomFilter, CountingBloomFilter};
+#[cfg(feature = "alloc")]
+mod decaying;
+#[cfg(feature = "alloc")]
+pub use decaying::{DecayingBloomFilter, RotationPolicy};
+mod fixed;
+pub use fixed::{BitWord, FixedBloomFilter};
+#[cfg(feature = "alloc")]
+mod independent;
+#[cfg(feature = "alloc")]
+pub use independent::IndependentBloomFilter;
+#[cfg(feature = "alloc")]
+mod int_filter;
+#[cfg(feature = "alloc")]
+pub use int_filter::IntBloomFilter;
+pub mod math;
+pub use math::{
+    achievable_fp, expected_density, expected_false_pos, optimal_hashes, optimal_hashes_capped,
+    optimal_size, optimal_size_bits, plan, FilterParams,
+};
+#[cfg(feature = "alloc")]
+mod profiling;
+#[cfg(feature = "alloc")]
+mod scalable;
+#[cfg(feature = "alloc")]
+use profiling::HashCallCounter;
+#[cfg(feature = "alloc")]
+pub use scalable::ScalableBloomFilter;
+#[cfg(feature = "alloc")]
+mod stable;
+#[cfg(feature = "alloc")]
+pub use stable::StableBloomFilter;
 
 #[cfg(feature = "loom")]
-pub(crate) use loom::sync::atomic::AtomicU64;
+pub(crate) use loom::sync::atomic::{AtomicU64, AtomicU8};
 
-#[cfg(not(feature = "loom"))]
-pub(crate) use portable_atomic::AtomicU64;
+#[cfg(all(feature = "alloc", not(feature = "loom")))]
+pub(crate) use portable_atomic::{AtomicU64, AtomicU8};
 
 #[cfg(all(feature = "loom", feature = "serde"))]
 compile_error!("features `loom` and `serde` are mutually exclusive");
 
+#[cfg(feature = "alloc")]
 macro_rules! impl_bloom {
     ($name:ident, $builder_bits:ident, $builder_fp:ident, $bitvec:ident, $bits:ty, $ismut:literal, $($m:ident)?) => {
         /// A space efficient approximate membership set data structure.
@@ -68,29 +132,47 @@ macro_rules! impl_bloom {
         ///     .items(["42", "🦀"].iter());
         /// ```
         #[derive(Debug, Clone)]
-        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize))]
         pub struct $name<S = DefaultHasher> {
             bits: $bitvec,
+            #[cfg_attr(feature = "serde", serde(rename = "num_hashes", serialize_with = "serde_num_hashes::serialize"))]
             num_hashes_minus_one: u32,
             hasher: S,
+            #[cfg_attr(feature = "serde", serde(skip))]
+            hash_calls: HashCallCounter,
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de, S: serde::Deserialize<'de>> serde::Deserialize<'de> for $name<S> {
+            fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                let (bits, num_hashes_minus_one, hasher) = serde_num_hashes::deserialize_parts(d, stringify!($name))?;
+                Ok(Self {
+                    bits: bits.into_iter().collect(),
+                    num_hashes_minus_one,
+                    hasher,
+                    hash_calls: HashCallCounter::default(),
+                })
+            }
         }
 
         impl $name {
             fn new_builder(num_bits: usize) -> $builder_bits {
                 assert!(num_bits > 0);
+                assert!(num_bits <= MAX_NUM_BITS, "num_bits must be at most {}", MAX_NUM_BITS);
                 // Only available in rust 1.73+
                 // let num_u64s = num_bits.div_ceil(64);
                 let num_u64s = (num_bits + 64 - 1) / 64;
                 $builder_bits {
-                    data: repeat(0).take(num_u64s).collect(),
+                    data: BuilderData::Zeros(num_u64s),
                     hasher: Default::default(),
                 }
             }
 
             fn new_from_vec(vec: Vec<u64>) -> $builder_bits {
                 assert!(!vec.is_empty());
+                assert!(vec.len() <= MAX_NUM_BITS / 64, "bit vector must have at most {} bits", MAX_NUM_BITS);
                 $builder_bits {
-                    data: vec,
+                    data: BuilderData::Bits(vec),
                     hasher: Default::default(),
                 }
             }
@@ -119,7 +201,8 @@ macro_rules! impl_bloom {
 
             /// Creates a builder instance to construct a [`Self`] with `num_bits` number of bits for tracking item membership.
             /// # Panics
-            /// Panics if the number of bits, `num_bits`, is 0.
+            /// Panics if the number of bits, `num_bits`, is 0 or greater than `usize::MAX - 63`
+            /// (the largest number of bits that is a whole number of `u64`s).
             ///
             /// # Examples
             /// ```
@@ -130,10 +213,26 @@ macro_rules! impl_bloom {
                 $name::new_builder(num_bits)
             }
 
+            /// Creates a builder instance to construct a [`Self`] using `num_bytes` bytes of memory for tracking item membership.
+            /// The number of bytes is rounded up to a whole number of `u64`s.
+            /// # Panics
+            /// Panics if the number of bytes, `num_bytes`, is 0 or its number of bits does not fit in a `usize`.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            #[doc = concat!("let filter = ", stringify!($name), "::with_num_bytes(4096).hashes(4);")]
+            /// assert_eq!(filter.num_bytes(), 4096);
+            /// ```
+            pub fn with_num_bytes(num_bytes: usize) -> $builder_bits {
+                let num_u64s = num_bytes / 8 + (num_bytes % 8 != 0) as usize;
+                $name::new_builder(num_u64s.saturating_mul(64))
+            }
+
             /// Creates a builder instance to construct a [`Self`] initialized with bit vector `bit_vec`.
             ///
             /// # Panics
-            /// Panics if the bit vector, `bit_vec`, is empty.
+            /// Panics if the bit vector, `bit_vec`, is empty or its number of bits does not fit in a `usize`.
             /// # Examples
             /// ```
             #[doc = concat!("use fastbloom::", stringify!($name), ";")]
@@ -148,9 +247,68 @@ macro_rules! impl_bloom {
             pub fn from_vec(bit_vec: Vec<u64>) -> $builder_bits {
                 $name::new_from_vec(bit_vec)
             }
+
+            /// Fallible version of [`from_vec`](Self::from_vec). Any non-empty bit vector is accepted, since bits
+            /// are not grouped into blocks.
+            ///
+            /// # Errors
+            /// Returns [`BuildError::EmptyBitVec`] if `bit_vec` is empty, and [`BuildError::TooManyBits`]
+            /// if its number of bits does not fit in a `usize`.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::{", stringify!($name), ", BuildError};")]
+            ///
+            #[doc = concat!("assert!(", stringify!($name), "::try_from_vec(vec![0; 3]).is_ok());")]
+            #[doc = concat!("assert_eq!(", stringify!($name), "::try_from_vec(vec![]).unwrap_err(), BuildError::EmptyBitVec);")]
+            /// ```
+            pub fn try_from_vec(bit_vec: Vec<u64>) -> Result<$builder_bits, BuildError> {
+                if bit_vec.is_empty() {
+                    return Err(BuildError::EmptyBitVec);
+                }
+                if bit_vec.len() > MAX_NUM_BITS / 64 {
+                    return Err(BuildError::TooManyBits);
+                }
+                Ok($name::new_from_vec(bit_vec))
+            }
         }
 
         impl<S: BuildHasher> $name<S> {
+            #[doc = concat!("Constructs a [`", stringify!($name), "`] directly from its parts, as returned by [`into_raw_parts`](Self::into_raw_parts).")]
+            ///
+            /// This allows Bloom filters to be stored in a custom format and restored without re-inserting items.
+            /// `bit_vec`, `num_hashes` and `hasher` must match the Bloom filter the parts were taken from.
+            ///
+            /// # Errors
+            /// Returns [`BuildError::EmptyBitVec`] if `bit_vec` is empty, [`BuildError::TooManyBits`]
+            /// if its number of bits does not fit in a `usize`, and [`BuildError::ZeroHashes`] if `num_hashes` is 0.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let orig = ", stringify!($name), "::with_false_pos(0.001).items([1, 2].iter());")]
+            /// let (bit_vec, num_hashes, hasher) = orig.into_raw_parts();
+            #[doc = concat!("let new = ", stringify!($name), "::from_raw_parts(bit_vec, num_hashes, hasher).unwrap();")]
+            ///
+            /// assert!(new.contains(&1));
+            /// assert!(new.contains(&2));
+            /// ```
+            pub fn from_raw_parts(bit_vec: Vec<u64>, num_hashes: u32, hasher: S) -> Result<Self, BuildError> {
+                if num_hashes == 0 {
+                    return Err(BuildError::ZeroHashes);
+                }
+                Ok($name::try_from_vec(bit_vec)?.hasher(hasher).hashes(num_hashes))
+            }
+
+            /// Decomposes this Bloom filter into its bit vector, number of hashes and hasher.
+            ///
+            /// The Bloom filter can be reconstructed with [`from_raw_parts`](Self::from_raw_parts).
+            pub fn into_raw_parts(self) -> (Vec<u64>, u32, S) {
+                let num_hashes = self.num_hashes();
+                (self.bits.into_vec(), num_hashes, self.hasher)
+            }
+
             /// Checks if an element is possibly in the Bloom filter.
             ///
             /// # Returns
@@ -170,6 +328,69 @@ macro_rules! impl_bloom {
                 self.contains_hash(self.source_hash(val))
             }
 
+            /// Checks if each of `items` is possibly in the Bloom filter, returning the answers in the same order as `items`.
+            ///
+            /// Items are processed in small groups: the first bit of every item in a group is loaded before any item's
+            /// remaining bits are, so the (independent) memory loads of different items overlap instead of
+            /// being paid one after another. For large Bloom filters this can be faster than calling [`contains`](Self::contains) in a loop.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let bloom = ", stringify!($name), "::with_false_pos(0.001).items([1, 2].iter());")]
+            /// assert_eq!(bloom.contains_many(&[1, 3, 2]), vec![true, false, true]);
+            /// ```
+            pub fn contains_many<'a, T: Hash + 'a, I: IntoIterator<Item = &'a T>>(&self, items: I) -> Vec<bool> {
+                const GROUP_SIZE: usize = 8;
+                let items = items.into_iter();
+                let mut result = Vec::with_capacity(items.size_hint().0);
+                let mut hashes = [0u64; GROUP_SIZE];
+                let mut len = 0;
+                for item in items {
+                    hashes[len] = self.source_hash(item);
+                    len += 1;
+                    if len == GROUP_SIZE {
+                        self.contains_group(&hashes, &mut result);
+                        len = 0;
+                    }
+                }
+                self.contains_group(&hashes[..len], &mut result);
+                result
+            }
+
+            /// Appends whether each hash in `hashes` is possibly in the Bloom filter to `result`. See [`Self::contains_many`].
+            #[inline]
+            fn contains_group(&self, hashes: &[u64], result: &mut Vec<bool>) {
+                let start = result.len();
+                result.extend(hashes.iter().map(|&h| self.bits.check(index(self.num_bits(), h))));
+                for (found, &h) in result[start..].iter_mut().zip(hashes) {
+                    if *found {
+                        *found = self.contains_hash(h);
+                    }
+                }
+            }
+
+            /// Checks if an element is possibly in the Bloom filter, like [`contains`](Self::contains),
+            /// but spells out the answer as a [`Membership`].
+            ///
+            /// # Examples
+            ///
+            /// ```
+            #[doc = concat!("use fastbloom::{", stringify!($name), ", Membership};")]
+            ///
+            #[doc = concat!("let bloom = ", stringify!($name), "::with_num_bits(1024).items([1, 2].iter());")]
+            /// assert_eq!(bloom.membership(&1), Membership::PossiblyPresent);
+            /// ```
+            #[inline]
+            pub fn membership(&self, val: &(impl Hash + ?Sized)) -> Membership {
+                match self.contains(val) {
+                    true => Membership::PossiblyPresent,
+                    false => Membership::DefinitelyAbsent,
+                }
+            }
+
             /// Checks if the hash of an element is possibly in the Bloom filter.
             /// That is the element is pre-hashed and all subsequent hashes are derived from this "source" hash.
             ///
@@ -178,16 +399,28 @@ macro_rules! impl_bloom {
             /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
             #[inline]
             pub fn contains_hash(&self, hash: u64) -> bool {
-                match self.bits.check(index(self.num_bits(), hash)) {
-                    false => false,
-                    true => {
-                        let mut hasher = DoubleHasher::new(hash);
-                        (0..self.num_hashes_minus_one).all(|_| {
-                            let h = hasher.next();
-                            self.bits.check(index(self.num_bits(), h))
-                        })
-                    }
-                }
+                indexes(self.num_bits(), hash, self.num_hashes_minus_one).all(|i| self.bits.check(i))
+            }
+
+            /// Returns the fraction of an element's bits that are set in the Bloom filter.
+            ///
+            /// `1.0` means all bits are set, i.e. the item is possibly in the Bloom filter,
+            /// and anything lower means the item is definitely not in the Bloom filter.
+            /// This can be used as a soft signal to rank candidate items.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let bloom = ", stringify!($name), "::with_num_bits(1024).items([1, 2, 3].iter());")]
+            /// assert_eq!(bloom.match_strength(&1), 1.0);
+            /// ```
+            pub fn match_strength(&self, val: &(impl Hash + ?Sized)) -> f32 {
+                let hash = self.source_hash(val);
+                let num_set = indexes(self.num_bits(), hash, self.num_hashes_minus_one)
+                    .filter(|&i| self.bits.check(i))
+                    .count();
+                num_set as f32 / self.num_hashes() as f32
             }
 
             /// Returns the number of hashes per item.
@@ -201,6 +434,70 @@ macro_rules! impl_bloom {
                 self.bits.num_bits()
             }
 
+            /// Returns the number of bytes of memory used by the Bloom filter's bits, i.e. `num_bits() / 8`.
+            pub fn num_bytes(&self) -> usize {
+                self.num_bits() / 8
+            }
+
+            /// Returns the total number of bytes of memory used by the Bloom filter:
+            /// its bits, see [`Self::num_bytes`], plus the size of the [`Self`] struct, including the hasher.
+            ///
+            /// The bits are stored in a boxed slice, so there is no spare capacity to account for.
+            /// Heap memory owned by the hasher is not included, since it can't be known for arbitrary hashers;
+            /// [`DefaultHasher`] owns none.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let filter = ", stringify!($name), "::with_num_bytes(4096).hashes(4);")]
+            #[doc = concat!("assert_eq!(filter.memory_usage(), 4096 + std::mem::size_of::<", stringify!($name), ">());")]
+            /// ```
+            pub fn memory_usage(&self) -> usize {
+                self.num_bytes() + core::mem::size_of::<Self>()
+            }
+
+            /// Returns `true` if no bits are set in the Bloom filter, i.e. no items have been inserted.
+            ///
+            /// The scan stops at the first non-zero word, so non-empty filters are typically detected quickly.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let ", $ismut, "bloom = ", stringify!($name), "::with_num_bits(1024).hashes(4);")]
+            /// assert!(bloom.is_empty());
+            /// bloom.insert(&2);
+            /// assert!(!bloom.is_empty());
+            /// ```
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.bits.is_empty()
+            }
+
+            /// Returns the number of bits set in the Bloom filter.
+            #[inline]
+            pub fn count_ones(&self) -> usize {
+                self.bits.count_ones()
+            }
+
+            /// Returns the fraction of bits set in the Bloom filter.
+            ///
+            /// The expected false positive rate grows with the density, so this can be used to monitor how full a Bloom filter is.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let bloom = ", stringify!($name), "::with_num_bits(1024).items([1, 2, 3].iter());")]
+            /// assert!(bloom.density() > 0.0);
+            /// assert_eq!(bloom.density(), bloom.count_ones() as f64 / 1024.0);
+            /// ```
+            #[inline]
+            pub fn density(&self) -> f64 {
+                self.count_ones() as f64 / self.num_bits() as f64
+            }
+
             /// Returns an iterator over the raw bit values of this Bloom filter.
             #[inline]
             pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
@@ -208,6 +505,9 @@ macro_rules! impl_bloom {
             }
 
             /// Returns the underlying slice of this Bloom filter's bit contents.
+            ///
+            /// The slice has `num_bits() / 64` words and, together with [`num_hashes`](Self::num_hashes) and the hasher,
+            /// can be used to reconstruct the Bloom filter with [`from_raw_parts`](Self::from_raw_parts).
             #[inline]
             pub fn as_slice(&self) -> &[$bits] {
                 self.bits.as_slice()
@@ -219,11 +519,150 @@ macro_rules! impl_bloom {
             /// This is useful for pre-computing hash values in order to store them or send them over the network.
             #[inline]
             pub fn source_hash(&self, val: &(impl Hash + ?Sized)) -> u64 {
+                self.hash_calls.increment();
                 let mut state = self.hasher.build_hasher();
                 val.hash(&mut state);
                 state.finish()
             }
 
+            /// Returns the number of times this Bloom filter has called `build_hasher`, i.e. the
+            /// number of items hashed by [`Self::insert`], [`Self::contains`] and [`Self::source_hash`].
+            ///
+            /// Only available with the `profiling` feature.
+            #[cfg(feature = "profiling")]
+            #[inline]
+            pub fn hash_call_count(&self) -> u64 {
+                self.hash_calls.get()
+            }
+
+            /// Checks that every item in `items`, which are supposed to have been inserted, is contained in the Bloom filter.
+            ///
+            /// A correctly used Bloom filter never has false negatives, so any reported false negative
+            /// indicates that the items were not all inserted, or that `self` uses a different hasher or
+            /// seed than the filter they were inserted into.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let bloom = ", stringify!($name), "::with_num_bits(1024).items([1, 2, 3].iter());")]
+            /// let result = bloom.self_test(&[1, 2, 3]);
+            /// assert!(result.is_ok());
+            /// assert_eq!(result.num_checked(), 3);
+            /// ```
+            pub fn self_test(&self, items: &[impl Hash]) -> SelfTestResult {
+                SelfTestResult {
+                    num_checked: items.len(),
+                    num_false_negatives: items.iter().filter(|x| !self.contains(x)).count(),
+                }
+            }
+
+            /// Returns how many more distinct items can be inserted while keeping the expected false positive rate
+            /// at or below `target_fp`, based on how many bits are currently set.
+            ///
+            /// The number of hashes is fixed once the Bloom filter is built, so this accounts for the items already
+            /// inserted without needing to know how many there were.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let ", $ismut, "bloom = ", stringify!($name), "::with_false_pos(0.01).expected_items(1000);")]
+            /// let before = bloom.capacity_headroom(0.01);
+            /// for x in 0..500 {
+            ///     bloom.insert(&x);
+            /// }
+            /// assert!(bloom.capacity_headroom(0.01) < before);
+            /// ```
+            pub fn capacity_headroom(&self, target_fp: f64) -> usize {
+                let num_bits = self.num_bits() as f64;
+                let density = self.density();
+                let max_density = math::pow(target_fp, 1.0 / self.num_hashes() as f64);
+                if density >= max_density {
+                    return 0;
+                }
+                // Each new item sets each bit with probability 1 - (1 - 1/m)^k, so after n more items
+                // density is 1 - (1 - density) * (1 - 1/m)^(k * n). Solve for the n reaching max density.
+                let per_item = self.num_hashes() as f64 * math::ln(1.0 - 1.0 / num_bits);
+                (math::ln((1.0 - max_density) / (1.0 - density)) / per_item) as usize
+            }
+
+            /// Returns an estimate of the number of distinct items inserted into the Bloom filter, based on how many bits are set.
+            ///
+            /// This uses the estimator from Swamidass & Baldi (2007): `-(m / k) * ln(1 - X / m)`, where `m` is the number of bits,
+            /// `k` is the number of hashes, and `X` is the number of bits set.
+            /// Returns [`f64::INFINITY`] if all bits are set, since any number of items could have been inserted.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let bloom = ", stringify!($name), "::with_false_pos(0.001).items((0..1000).collect::<Vec<_>>().iter());")]
+            /// let estimate = bloom.estimate_cardinality();
+            /// assert!(estimate > 950.0 && estimate < 1050.0);
+            /// ```
+            pub fn estimate_cardinality(&self) -> f64 {
+                let num_bits = self.num_bits() as f64;
+                let num_ones = self.count_ones() as f64;
+                if num_ones >= num_bits {
+                    return f64::INFINITY;
+                }
+                -(num_bits / self.num_hashes() as f64) * math::ln(1.0 - num_ones / num_bits)
+            }
+
+            /// Returns an estimate of the number of distinct items inserted into `self` but not into `other`.
+            ///
+            /// The estimate is `|A ∪ B| - |B|`, each estimated as in [`Self::estimate_cardinality`]: `|B|` from the bits set
+            /// in `other`, and `|A ∪ B|` from those plus the bits set in `self` but not in `other` (`A AND NOT B`).
+            /// The error grows as the Bloom filters approach saturation, since each extra set bit then stands for
+            /// more items; returns [`f64::INFINITY`] if all bits are set in their union.
+            ///
+            /// # Panics
+            /// Panics if the other Bloom filter has a different number of bits, number of hashes or hasher than `self`.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let a = ", stringify!($name), "::with_num_bits(1 << 16).seed(&1).items((0..1000).collect::<Vec<_>>().iter());")]
+            #[doc = concat!("let b = ", stringify!($name), "::with_num_bits(1 << 16).seed(&1).items((400..1400).collect::<Vec<_>>().iter());")]
+            /// let estimate = a.estimate_difference_cardinality(&b);
+            /// assert!(estimate > 360.0 && estimate < 440.0);
+            /// ```
+            pub fn estimate_difference_cardinality(&self, other: &Self) -> f64 {
+                self.assert_compatible(other);
+                let num_bits = self.num_bits() as f64;
+                let (mut other_ones, mut only_self_ones) = (0, 0);
+                for (x, y) in self.bits.iter().zip(other.bits.iter()) {
+                    other_ones += y.count_ones() as usize;
+                    only_self_ones += (x & !y).count_ones() as usize;
+                }
+                let other_zeros = num_bits - other_ones as f64;
+                let union_zeros = other_zeros - only_self_ones as f64;
+                if union_zeros <= 0.0 {
+                    return f64::INFINITY;
+                }
+                // -(m / k) * ln(1 - |A ∪ B| / m) + (m / k) * ln(1 - |B| / m)
+                -(num_bits / self.num_hashes() as f64) * math::ln(union_zeros / other_zeros)
+            }
+
+            /// Returns the estimated current false positive rate of this Bloom filter, based on how many bits are set.
+            ///
+            /// Unlike [`Self::expected_false_pos`], this doesn't need the number of items, and reflects the actual
+            /// state of the bits, so it can be used to detect a Bloom filter filling up beyond its design.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let bloom = ", stringify!($name), "::with_false_pos(0.01).items((0..1000).collect::<Vec<_>>().iter());")]
+            /// assert!(bloom.current_false_pos() < 0.02);
+            /// ```
+            #[inline]
+            pub fn current_false_pos(&self) -> f64 {
+                crate::expected_false_pos(self.num_hashes(), self.density())
+            }
+
             /// Returns the expected false positive rate of this bloom filter containing `num_items`.
             pub fn expected_false_pos(&self, num_items: usize) -> f64 {
                 let density = crate::expected_density(self.num_hashes(), self.num_bits(), num_items);
@@ -237,12 +676,17 @@ macro_rules! impl_bloom {
             /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
             /// `false` otherwise.
             ///
+            /// `false` means at least one previously unset bit was set, i.e. the item was newly added.
+            /// This makes a separate `contains` check unnecessary for "insert if absent" workloads,
+            /// since the item is only hashed once.
+            ///
             /// # Examples
             /// ```
             #[doc = concat!("use fastbloom::", stringify!($name), ";")]
             ///
             #[doc = concat!("let ", $ismut, "bloom = ", stringify!($name), "::with_num_bits(1024).hashes(4);")]
-            /// bloom.insert(&2);
+            /// assert!(!bloom.insert(&2)); // newly added
+            /// assert!(bloom.insert(&2)); // already present
             /// assert!(bloom.contains(&2));
             /// ```
             #[inline]
@@ -250,9 +694,56 @@ macro_rules! impl_bloom {
                 self.insert_hash(self.source_hash(val))
             }
 
+            /// Returns an iterator over the items of `iter` that were not already in the Bloom filter, inserting each one.
+            ///
+            /// This removes duplicates from a stream in a single pass using the Bloom filter's memory instead of a set of all items seen.
+            /// Like [`contains_or_insert`](Self::contains_or_insert), false positives mean that some first occurrences may be skipped.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let bloom = ", stringify!($name), "::with_false_pos(0.001).expected_items(100);")]
+            /// let unique: Vec<_> = bloom.dedup_iter([1, 2, 1, 3, 2]).collect();
+            /// assert_eq!(unique, vec![1, 2, 3]);
+            /// ```
+            pub fn dedup_iter<T: Hash, I: IntoIterator<Item = T>>(self, iter: I) -> impl Iterator<Item = T> {
+                let $($m)? bloom = self;
+                iter.into_iter().filter(move |x| !bloom.insert(x))
+            }
+
+            /// Checks if an element is possibly in the Bloom filter, inserting it if not, hashing it only once.
+            ///
+            /// This is the same operation as [`insert`](Self::insert), named for "have I seen this?" deduplication loops.
+            /// In [`AtomicBloomFilter`], bits are set with an atomic fetch-or, so if several threads race to insert
+            /// the same absent item, at least one of them gets `false`.
+            ///
+            /// # Returns
+            ///
+            /// `true` if the item was (probably) already present, `false` if it was newly inserted.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let ", $ismut, "seen = ", stringify!($name), "::with_false_pos(0.001).expected_items(100);")]
+            /// let unique: Vec<_> = [1, 2, 1, 3, 2]
+            ///     .into_iter()
+            ///     .filter(|x| !seen.contains_or_insert(x))
+            ///     .collect();
+            /// assert_eq!(unique, vec![1, 2, 3]);
+            /// ```
+            #[inline]
+            pub fn contains_or_insert(&$($m)? self, val: &(impl Hash + ?Sized)) -> bool {
+                self.insert(val)
+            }
+
             /// Inserts the hash of an element into the Bloom filter.
             /// That is the element is pre-hashed and all subsequent hashes are derived from this "source" hash.
             ///
+            /// The Bloom filter's hasher is bypassed, so existing 64-bit digests can be inserted directly,
+            /// e.g. to use the Bloom filter as a set of `u64` keys. Well-distributed hashes are required for an accurate false positive rate.
+            ///
             /// # Returns
             ///
             /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
@@ -260,62 +751,229 @@ macro_rules! impl_bloom {
             #[inline]
             pub fn insert_hash(&$($m)? self, hash: u64) -> bool {
                 let mut previously_contained = true;
-                previously_contained &= self.bits.set(index(self.num_bits(), hash));
-                let mut hasher = DoubleHasher::new(hash);
-                for _ in 0..self.num_hashes_minus_one {
-                    let h = hasher.next();
-                    previously_contained &= self.bits.set(index(self.num_bits(), h));
+                for i in indexes(self.num_bits(), hash, self.num_hashes_minus_one) {
+                    previously_contained &= self.bits.set(i);
                 }
                 previously_contained
             }
 
-            /// Inserts all the items in `iter` into the `self`.
+            /// Inserts a composite item, given the hashes of its fields, into the Bloom filter, regardless of field order.
+            ///
+            /// The field hashes are combined with XOR, so `(a, b)` and `(b, a)` are the same item. This also means that
+            /// different combinations of fields can collide, e.g. a pair of equal fields cancels out, so `(a, a, b)` is the same item as `(b)`.
+            /// Field hashes should be computed with [`Self::source_hash`].
+            ///
+            /// # Returns
+            ///
+            /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
+            /// `false` otherwise.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let ", $ismut, "bloom = ", stringify!($name), "::with_num_bits(1024).hashes(4);")]
+            /// let (a, b) = (bloom.source_hash("a"), bloom.source_hash("b"));
+            /// bloom.insert_unordered([a, b]);
+            /// assert!(bloom.contains_unordered([b, a]));
+            /// ```
             #[inline]
-            pub fn insert_all<'a, T: Hash + 'a, I: IntoIterator<Item = &'a T>>(&$($m)? self, iter: I) {
-                for val in iter {
-                    self.insert(val);
-                }
+            pub fn insert_unordered<I: IntoIterator<Item = u64>>(&$($m)? self, field_hashes: I) -> bool {
+                self.insert_hash(field_hashes.into_iter().fold(0, |acc, h| acc ^ h))
             }
 
-            /// Clear all of the bits in the Bloom filter, removing all items.
+            /// Checks if a composite item, given the hashes of its fields, is possibly in the Bloom filter, regardless of field order.
+            /// See [`Self::insert_unordered`].
+            ///
+            /// # Returns
+            ///
+            /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
             #[inline]
-            pub fn clear(&$($m)? self) {
-                self.bits.clear();
+            pub fn contains_unordered<I: IntoIterator<Item = u64>>(&self, field_hashes: I) -> bool {
+                self.contains_hash(field_hashes.into_iter().fold(0, |acc, h| acc ^ h))
             }
 
-            /// Unions `other` into `self`. The hashers of both Bloom filters must be identical (this is not enforced!).
+            /// Inserts the bytes of `val` into the Bloom filter. Anything viewable as bytes, such as `&str`, `String`,
+            /// `&[u8]` and `Vec<u8>`, is hashed the same way, so it can be checked with [`Self::contains_ref`] using any other such type.
             ///
-            /// # Panics
-            /// Panics if the other Bloom filter has a different number of bits or hashes than `self`.
+            /// Note that this hashes `val` as `[u8]`, which differs from how e.g. `str` is hashed by [`Self::insert`].
             ///
-            /// # Example
+            /// # Returns
+            ///
+            /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
+            /// `false` otherwise.
+            ///
+            /// # Examples
             /// ```
             #[doc = concat!("use fastbloom::", stringify!($name), ";")]
             ///
-            #[doc = concat!("let ", $ismut, "bloom = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(4);")]
-            #[doc = concat!("let ", $ismut, "other = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(4);")]
-            /// for x in 0..=1000 {
-            ///     bloom.insert(&x);
-            /// }
-            /// for x in 500..=1500 {
-            ///     bloom.insert(&x);
-            /// }
-            /// bloom.union(&other);
-            ///
-            /// for x in 0..=2000 {
-            ///     assert_eq!(bloom.contains(&x), bloom.contains(&x) || other.contains(&x));
-            /// }
+            #[doc = concat!("let ", $ismut, "bloom = ", stringify!($name), "::with_num_bits(1024).hashes(4);")]
+            /// bloom.insert_ref("x");
+            /// assert!(bloom.contains_ref(String::from("x")));
+            /// assert!(bloom.contains_ref(b"x"));
             /// ```
             #[inline]
-            pub fn union(&$($m)? self, other: &Self) {
-                assert_eq!(
-                    self.num_hashes(),
-                    other.num_hashes(),
+            pub fn insert_ref<B: AsRef<[u8]>>(&$($m)? self, val: B) -> bool {
+                self.insert(val.as_ref())
+            }
+
+            /// Checks if the bytes of `val` are possibly in the Bloom filter. See [`Self::insert_ref`].
+            ///
+            /// # Returns
+            ///
+            /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
+            #[inline]
+            pub fn contains_ref<B: AsRef<[u8]>>(&self, val: B) -> bool {
+                self.contains(val.as_ref())
+            }
+
+            /// Inserts all the items in `iter` into the `self`.
+            #[inline]
+            pub fn insert_all<'a, T: Hash + 'a, I: IntoIterator<Item = &'a T>>(&$($m)? self, iter: I) {
+                for val in iter {
+                    self.insert(val);
+                }
+            }
+
+            /// Inserts all the pre-computed source hashes in `hashes` into `self`. See [`Self::insert_hash`].
+            ///
+            /// This decouples hashing from setting bits, e.g. when hashes are computed in a separate pipeline stage.
+            /// The hashes must be computed with the same hasher as this Bloom filter, e.g. via [`Self::source_hash`].
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let ", $ismut, "bloom = ", stringify!($name), "::with_num_bits(1024).hashes(4);")]
+            /// let hashes: Vec<u64> = (0..10).map(|x| bloom.source_hash(&x)).collect();
+            /// bloom.insert_hashes(&hashes);
+            /// assert!((0..10).all(|x| bloom.contains(&x)));
+            /// ```
+            #[inline]
+            pub fn insert_hashes(&$($m)? self, hashes: &[u64]) {
+                for &hash in hashes {
+                    self.insert_hash(hash);
+                }
+            }
+
+            /// Clear all of the bits in the Bloom filter, removing all items.
+            ///
+            /// The underlying memory, number of hashes and hasher are kept, so the Bloom filter can be reused without reallocating.
+            ///
+            /// Words are zeroed one at a time with relaxed ordering. An [`AtomicBloomFilter`] can be cleared while other
+            /// threads check it, without a lock; they may observe a partially cleared Bloom filter, which only
+            /// means some items are briefly contained or not contained depending on which words were zeroed so far.
+            #[inline]
+            pub fn clear(&$($m)? self) {
+                self.bits.clear();
+            }
+
+            /// Unions `other` into `self`. The hashers of both Bloom filters must be identical (this is not enforced!).
+            ///
+            /// To get a new Bloom filter instead of modifying `self`, use `&a | &b`, the counterpart of
+            /// [`intersection`](Self::intersection). A by-value method would have to be named `union` too.
+            ///
+            /// # Panics
+            /// Panics if the other Bloom filter has a different number of bits or hashes than `self`.
+            ///
+            /// # Example
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let ", $ismut, "bloom = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(4);")]
+            #[doc = concat!("let ", $ismut, "other = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(4);")]
+            /// for x in 0..=1000 {
+            ///     bloom.insert(&x);
+            /// }
+            /// for x in 500..=1500 {
+            ///     bloom.insert(&x);
+            /// }
+            /// bloom.union(&other);
+            ///
+            /// for x in 0..=2000 {
+            ///     assert_eq!(bloom.contains(&x), bloom.contains(&x) || other.contains(&x));
+            /// }
+            /// ```
+            #[inline]
+            pub fn union(&$($m)? self, other: &Self) {
+                assert_eq!(
+                    self.num_hashes(),
+                    other.num_hashes(),
                     "expected same number of hashes"
                 );
                 self.bits.union(&other.bits);
             }
 
+            /// Unions `other` into `self`, returning the number of bits that were newly set by the merge.
+            /// The hashers of both Bloom filters must be identical (this is not enforced!).
+            ///
+            /// A return value of 0 means `other` contributed nothing new, e.g. because it is a redundant shard.
+            ///
+            /// # Errors
+            /// Returns [`MergeError`] and leaves `self` unchanged if the other Bloom filter has a different number of bits or hashes than `self`.
+            ///
+            /// # Example
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let ", $ismut, "bloom = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(4);")]
+            #[doc = concat!("let ", $ismut, "other = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(4);")]
+            /// other.insert(&1);
+            /// assert_eq!(bloom.union_counting(&other), Ok(4));
+            /// assert_eq!(bloom.union_counting(&other), Ok(0));
+            /// ```
+            pub fn union_counting(&$($m)? self, other: &Self) -> Result<usize, MergeError> {
+                if self.num_bits() != other.num_bits() {
+                    return Err(MergeError::MismatchedBits {
+                        num_bits: self.num_bits(),
+                        other_num_bits: other.num_bits(),
+                    });
+                }
+                if self.num_hashes() != other.num_hashes() {
+                    return Err(MergeError::MismatchedHashes {
+                        num_hashes: self.num_hashes(),
+                        other_num_hashes: other.num_hashes(),
+                    });
+                }
+                Ok(self.bits.union_counting(&other.bits))
+            }
+
+            /// Unions `other` into `self`, even if `other` has a different number of hashes than `self`.
+            /// The hashers of both Bloom filters must be identical (this is not enforced!).
+            ///
+            /// Subsequent checks use `self`'s number of hashes. If the number of hashes differ,
+            /// items from `self` are still always contained, but items from `other` may not be, and the false positive
+            /// rate is higher than that of a Bloom filter built with the combined items.
+            ///
+            /// # Returns
+            ///
+            /// [`UnionResult::MismatchedHashes`] if the number of hashes differ, [`UnionResult::Exact`] otherwise.
+            ///
+            /// # Panics
+            /// Panics if the other Bloom filter has a different number of bits than `self`.
+            ///
+            /// # Example
+            /// ```
+            #[doc = concat!("use fastbloom::{", stringify!($name), ", UnionResult};")]
+            ///
+            #[doc = concat!("let ", $ismut, "bloom = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(4);")]
+            #[doc = concat!("let other = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(5);")]
+            /// let result = bloom.union_lossy(&other);
+            /// assert_eq!(result, UnionResult::MismatchedHashes { num_hashes: 4, other_num_hashes: 5 });
+            /// ```
+            #[inline]
+            pub fn union_lossy(&$($m)? self, other: &Self) -> UnionResult {
+                self.bits.union(&other.bits);
+                if self.num_hashes() == other.num_hashes() {
+                    UnionResult::Exact
+                } else {
+                    UnionResult::MismatchedHashes {
+                        num_hashes: self.num_hashes(),
+                        other_num_hashes: other.num_hashes(),
+                    }
+                }
+            }
+
             /// Intersects `other` onto `self`. The hashers of both Bloom filters must be identical (this is not enforced!).
             ///
             /// # Panics
@@ -348,12 +1006,334 @@ macro_rules! impl_bloom {
                 );
                 self.bits.intersect(&other.bits);
             }
+
+            /// Returns `true` if every bit set in `self` is also set in `other`.
+            ///
+            /// This is about bit containment, not set containment: if `self`'s items were all inserted into `other`,
+            /// this is `true`, but `true` doesn't guarantee it, since different items can set the same bits.
+            ///
+            /// # Panics
+            /// Panics if the other Bloom filter has a different number of bits, number of hashes or hasher than `self`.
+            ///
+            /// # Example
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let ", $ismut, "daily = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(4);")]
+            #[doc = concat!("let ", $ismut, "all_time = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(4);")]
+            /// daily.insert(&1);
+            /// all_time.insert(&1);
+            /// all_time.insert(&2);
+            /// assert!(daily.is_subset(&all_time));
+            /// assert!(!all_time.is_subset(&daily));
+            /// ```
+            pub fn is_subset(&self, other: &Self) -> bool {
+                self.assert_compatible(other);
+                self.bits.iter().zip(other.bits.iter()).all(|(x, y)| x & !y == 0)
+            }
+
+            /// Clears every bit of `self` that is not set in `allowed`, so `self` only keeps bits also present in `allowed`.
+            ///
+            /// This is the same operation as [`intersect`](Self::intersect), with a guarantee that matters when masking:
+            /// afterwards, any item that `allowed` reports as absent is also absent from `self`, so masking can never
+            /// make `self` report an item outside of `allowed`. Items of `self` that `allowed` doesn't contain may lose bits
+            /// and no longer be contained.
+            ///
+            /// # Panics
+            /// Panics if `allowed` has a different number of bits, number of hashes or hasher than `self`.
+            ///
+            /// # Example
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let ", $ismut, "bloom = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(4);")]
+            #[doc = concat!("let ", $ismut, "allowed = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(4);")]
+            /// bloom.insert(&1);
+            /// bloom.insert(&2);
+            /// allowed.insert(&1);
+            /// bloom.mask_with(&allowed);
+            /// assert!(bloom.contains(&1));
+            /// assert!(!bloom.contains(&2));
+            /// assert!(bloom.is_subset(&allowed));
+            /// ```
+            pub fn mask_with(&$($m)? self, allowed: &Self) {
+                self.assert_compatible(allowed);
+                self.bits.intersect(&allowed.bits);
+            }
+        }
+
+        impl<S: BuildHasher + Clone> $name<S> {
+            /// Builds the smallest Bloom filter containing all values in `items` that meets the target false positive rate `fp`,
+            /// using the same hasher as `self`.
+            ///
+            /// Bits can't be removed from a Bloom filter without losing items, so an over-provisioned Bloom filter
+            /// can only be shrunk by rebuilding it from its items.
+            ///
+            /// # Panics
+            /// Panics if the false positive rate, `fp`, is 0.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            /// let items = [1, 2, 3];
+            #[doc = concat!("let bloom = ", stringify!($name), "::with_num_bits(1 << 20).items(items.iter());")]
+            /// let tight = bloom.rebuild_tightened(items.iter(), 0.001);
+            /// assert!(tight.num_bits() < bloom.num_bits());
+            /// assert!(tight.contains(&1));
+            /// ```
+            pub fn rebuild_tightened<'a, H: Hash + 'a, I: IntoIterator<IntoIter = impl ExactSizeIterator<Item = &'a H>>>(
+                &self,
+                items: I,
+                fp: f64,
+            ) -> Self {
+                $name::new_with_false_pos(fp)
+                    .hasher(self.hasher.clone())
+                    .items(items)
+            }
+        }
+
+        impl $name {
+            /// Builds an empty Bloom filter with the same number of bits and hashes as `self`, seeded with `new_seed`,
+            /// and inserts all values in `items`, e.g. to rotate seeds.
+            ///
+            /// Only Bloom filters with the default hasher can be seeded; for other hashers, build the new Bloom filter
+            #[doc = concat!("with [`", stringify!($name), "::with_num_bits`] and [`hasher`](BuilderWithBits::hasher) instead.")]
+            ///
+            /// A Bloom filter can't be migrated to a new seed from its bits alone: the new seed sends each item to
+            /// unrelated bits, so rearranging the existing bits can't produce them. `items` must therefore hold every
+            /// item that should be in the new Bloom filter, typically the ones originally inserted into `self`.
+            /// Items that are not supplied are not contained in the returned Bloom filter.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            /// let items = [1, 2, 3];
+            #[doc = concat!("let old = ", stringify!($name), "::with_num_bits(1024).seed(&1).items(items.iter());")]
+            /// let new = old.with_new_seed_rebuild(items.iter(), &2);
+            /// assert_eq!(new.num_bits(), old.num_bits());
+            /// assert_eq!(new.num_hashes(), old.num_hashes());
+            /// assert!(new.contains(&1));
+            /// ```
+            pub fn with_new_seed_rebuild<'a, H: Hash + 'a, I: IntoIterator<Item = &'a H>>(
+                &self,
+                items: I,
+                new_seed: &u128,
+            ) -> Self {
+                let $($m)? filter = $name::new_builder(self.num_bits())
+                    .seed(new_seed)
+                    .hashes(self.num_hashes());
+                filter.insert_all(items);
+                filter
+            }
+        }
+
+        impl<S: BuildHasher> $name<S> {
+            /// Panics unless `self` and `other` have the same number of bits, number of hashes and hasher.
+            ///
+            /// Hashers are compared by the hash they produce for a fixed value, which catches different seeds.
+            fn assert_compatible(&self, other: &Self) {
+                assert_eq!(self.num_bits(), other.num_bits(), "expected same number of bits");
+                assert_eq!(self.num_hashes(), other.num_hashes(), "expected same number of hashes");
+                assert_eq!(self.hasher_fingerprint(), other.hasher_fingerprint(), "expected same hasher");
+            }
+
+            /// Returns the hash of a fixed value, without counting it as a hash call.
+            fn hasher_fingerprint(&self) -> u64 {
+                let mut state = self.hasher.build_hasher();
+                0u64.hash(&mut state);
+                state.finish()
+            }
+        }
+
+        impl<S: BuildHasher + Clone> BitOr for &$name<S> {
+            type Output = $name<S>;
+
+            #[doc = concat!("Returns a new [`", stringify!($name), "`] containing all items of both Bloom filters.")]
+            #[doc = concat!("Use [`union`](", stringify!($name), "::union) to union in place without allocating.")]
+            ///
+            /// # Panics
+            /// Panics if the Bloom filters have a different number of bits, number of hashes or hasher (e.g. a different seed).
+            ///
+            /// # Example
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let a = ", stringify!($name), "::with_num_bits(4096).seed(&1).items([1, 2].iter());")]
+            #[doc = concat!("let b = ", stringify!($name), "::with_num_bits(4096).seed(&1).items([3, 4].iter());")]
+            /// let union = &a | &b;
+            /// assert!((1..=4).all(|x| union.contains(&x)));
+            /// ```
+            fn bitor(self, other: Self) -> $name<S> {
+                self.assert_compatible(other);
+                let $($m)? result = self.clone();
+                result.union(other);
+                result
+            }
+        }
+
+        impl<S: BuildHasher> BitOrAssign<&$name<S>> for $name<S> {
+            /// Unions `other` into `self` in place, without allocating. See [`union`](Self::union).
+            ///
+            /// # Panics
+            /// Panics if the Bloom filters have a different number of bits, number of hashes or hasher (e.g. a different seed).
+            ///
+            /// # Example
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let mut acc = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(4);")]
+            /// for shard in [[1, 2], [3, 4]] {
+            #[doc = concat!("    let ", $ismut, "filter = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(4);")]
+            ///     for x in shard {
+            ///         filter.insert(&x);
+            ///     }
+            ///     acc |= &filter;
+            /// }
+            /// assert!((1..=4).all(|x| acc.contains(&x)));
+            /// ```
+            fn bitor_assign(&mut self, other: &$name<S>) {
+                self.assert_compatible(other);
+                self.union(other);
+            }
+        }
+
+        impl<S: BuildHasher> BitAndAssign<&$name<S>> for $name<S> {
+            /// Intersects `other` onto `self` in place, without allocating. See [`intersect`](Self::intersect).
+            ///
+            /// Folding many Bloom filters this way keeps every item contained in all of them, e.g. keys present in all shards.
+            /// Bits set by different items in each Bloom filter also survive, so the result has more false positives than
+            /// a Bloom filter built from only the common items, especially when the inputs are dense.
+            ///
+            /// # Panics
+            /// Panics if the Bloom filters have a different number of bits, number of hashes or hasher (e.g. a different seed).
+            ///
+            /// # Example
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let mut acc = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(4);")]
+            /// for x in 0..10 {
+            ///     acc.insert(&x);
+            /// }
+            /// for shard in [0..8, 2..10] {
+            #[doc = concat!("    let ", $ismut, "filter = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(4);")]
+            ///     for x in shard {
+            ///         filter.insert(&x);
+            ///     }
+            ///     acc &= &filter;
+            /// }
+            /// assert!((2..8).all(|x| acc.contains(&x)));
+            /// ```
+            fn bitand_assign(&mut self, other: &$name<S>) {
+                self.assert_compatible(other);
+                self.intersect(other);
+            }
+        }
+
+        impl<S: BuildHasher> FromIterator<$name<S>> for $name<S> {
+            /// Unions all Bloom filters into the first one, e.g. to combine per-partition Bloom filters.
+            ///
+            /// # Panics
+            /// Panics if there are no Bloom filters, or if they have a different number of bits, number of hashes or hasher.
+            ///
+            /// # Example
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            /// let partitions = [0..10, 10..20, 20..30].map(|range| {
+            #[doc = concat!("    let ", $ismut, "filter = ", stringify!($name), "::with_num_bits(4096).seed(&1).hashes(4);")]
+            ///     for x in range {
+            ///         filter.insert(&x);
+            ///     }
+            ///     filter
+            /// });
+            #[doc = concat!("let combined: ", stringify!($name), " = partitions.into_iter().collect();")]
+            /// assert!((0..30).all(|x| combined.contains(&x)));
+            /// ```
+            fn from_iter<I: IntoIterator<Item = $name<S>>>(iter: I) -> Self {
+                let mut iter = iter.into_iter();
+                let mut combined = iter.next().expect("expected at least one Bloom filter");
+                for filter in iter {
+                    combined |= &filter;
+                }
+                combined
+            }
+        }
+
+        impl<S: BuildHasher + Clone> $name<S> {
+            #[doc = concat!("Returns a new [`", stringify!($name), "`] approximating the items contained in both Bloom filters.")]
+            /// `&a & &b` is equivalent. Use [`intersect`](Self::intersect) to intersect in place without allocating.
+            ///
+            /// Every item in both Bloom filters is contained in the result. However, the result has a higher
+            /// false positive rate than a Bloom filter built from only the common items: it keeps bits set by
+            /// items that are in only one of the filters, and can even report items that are in neither.
+            ///
+            /// # Panics
+            /// Panics if the Bloom filters have a different number of bits, number of hashes or hasher (e.g. a different seed).
+            ///
+            /// # Example
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let a = ", stringify!($name), "::with_num_bits(4096).seed(&1).items([1, 2, 3].iter());")]
+            #[doc = concat!("let b = ", stringify!($name), "::with_num_bits(4096).seed(&1).items([2, 3, 4].iter());")]
+            /// let intersection = a.intersection(&b);
+            /// assert!(intersection.contains(&2));
+            /// assert!(intersection.contains(&3));
+            /// ```
+            pub fn intersection(&self, other: &Self) -> Self {
+                self.assert_compatible(other);
+                let $($m)? result = self.clone();
+                result.intersect(other);
+                result
+            }
+        }
+
+        impl<S: BuildHasher + Clone> BitAnd for &$name<S> {
+            type Output = $name<S>;
+
+            #[doc = concat!("Returns a new [`", stringify!($name), "`] approximating the items contained in both Bloom filters.")]
+            #[doc = concat!("See [`intersection`](", stringify!($name), "::intersection).")]
+            ///
+            /// # Panics
+            /// Panics if the Bloom filters have a different number of bits, number of hashes or hasher (e.g. a different seed).
+            ///
+            /// # Example
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let a = ", stringify!($name), "::with_num_bits(4096).seed(&1).items([1, 2, 3].iter());")]
+            #[doc = concat!("let b = ", stringify!($name), "::with_num_bits(4096).seed(&1).items([2, 3, 4].iter());")]
+            /// let intersection = &a & &b;
+            /// assert!(intersection.contains(&2));
+            /// assert!(intersection.contains(&3));
+            /// ```
+            #[inline]
+            fn bitand(self, other: Self) -> $name<S> {
+                self.intersection(other)
+            }
         }
 
         impl<T, S: BuildHasher> Extend<T> for $name<S>
         where
             T: Hash,
         {
+            /// Inserts every item of `iter`.
+            ///
+            /// References hash the same as the values they point to, so items can be inserted by reference,
+            /// e.g. `bloom.extend(vec.iter())`, without moving or cloning them.
+            ///
+            /// # Example
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            /// let keys = vec![String::from("42"), String::from("🦀")];
+            #[doc = concat!("let mut bloom = ", stringify!($name), "::with_num_bits(1024).hashes(4);")]
+            /// bloom.extend(keys.iter());
+            /// assert!(keys.iter().all(|key| bloom.contains(key)));
+            /// ```
             #[inline]
             fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
                 for val in iter {
@@ -362,15 +1342,42 @@ macro_rules! impl_bloom {
             }
         }
 
+        impl<T: Hash> FromIterator<T> for $name {
+            /// Creates a [`Self`] containing all items, sized for a 1% false positive rate.
+            ///
+            /// The items are first collected into a temporary `Vec` to learn how many there are,
+            /// so this allocates; prefer [`Self::with_false_pos`] and `.items(...)` when the number of items is already known.
+            ///
+            /// # Examples
+            /// ```
+            #[doc = concat!("use fastbloom::", stringify!($name), ";")]
+            ///
+            #[doc = concat!("let filter: ", stringify!($name), " = (0..100).filter(|x| x % 2 == 0).collect();")]
+            /// assert!(filter.contains(&42));
+            /// ```
+            fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+                let items: Vec<T> = iter.into_iter().collect();
+                $name::with_false_pos(DEFAULT_FALSE_POS).items(items.iter())
+            }
+        }
+
         impl<S: BuildHasher> PartialEq for $name<S> {
+            /// Returns `true` if both Bloom filters have the same bits, number of hashes and hasher.
+            ///
+            /// Bloom filters with equal bits but different hashers (e.g. different seeds) are not equal,
+            /// since they answer [`contains`](Self::contains) differently for the same item.
+            /// Hashers are compared by the hash they produce for a fixed value.
             fn eq(&self, other: &Self) -> bool {
-                self.bits == other.bits && self.num_hashes() == other.num_hashes()
+                self.bits == other.bits
+                    && self.num_hashes() == other.num_hashes()
+                    && self.hasher_fingerprint() == other.hasher_fingerprint()
             }
         }
         impl<S: BuildHasher> Eq for $name<S> {}
     };
 }
 
+#[cfg(feature = "alloc")]
 impl_bloom!(
     BloomFilter,
     BuilderWithBits,
@@ -380,6 +1387,7 @@ impl_bloom!(
     "mut ",
     mut
 );
+#[cfg(feature = "alloc")]
 impl_bloom!(
     AtomicBloomFilter,
     AtomicBuilderWithBits,
@@ -389,6 +1397,522 @@ impl_bloom!(
     "",
 );
 
+#[cfg(feature = "alloc")]
+impl<S: BuildHasher> BloomFilter<S> {
+    /// Returns the underlying bit contents of this Bloom filter as bytes, e.g. for writing to disk.
+    ///
+    /// The bytes are the words of [`as_slice`](Self::as_slice) in native-endian order, so they are only portable
+    /// between machines of the same endianness. Use `u64::from_ne_bytes` to read the words back.
+    ///
+    /// # Example
+    /// ```
+    /// use fastbloom::BloomFilter;
+    ///
+    /// let bloom = BloomFilter::with_num_bits(1024).items([1, 2].iter());
+    /// let bytes = bloom.as_bytes();
+    /// assert_eq!(bytes.len(), 1024 / 8);
+    ///
+    /// let words: Vec<u64> = bytes
+    ///     .chunks_exact(8)
+    ///     .map(|chunk| u64::from_ne_bytes(chunk.try_into().unwrap()))
+    ///     .collect();
+    /// assert_eq!(words, bloom.as_slice());
+    /// ```
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        let words = self.as_slice();
+        // SAFETY: `u64` has no padding or invalid bit patterns, `u8` has an alignment of 1,
+        // and the byte slice covers exactly the memory of `words` for the same lifetime.
+        unsafe {
+            core::slice::from_raw_parts(words.as_ptr().cast::<u8>(), core::mem::size_of_val(words))
+        }
+    }
+
+    /// Converts this Bloom filter into an [`AtomicBloomFilter`] with the same bits, number of hashes and hasher,
+    /// e.g. to share a Bloom filter built on one thread for concurrent inserts.
+    ///
+    /// No items are rehashed: the bit vector is moved over word by word, so both Bloom filters give the same membership answers.
+    /// The conversion reuses the bit vector's allocation where `u64` and [`AtomicU64`](core::sync::atomic::AtomicU64)
+    /// have the same layout, as on 64-bit targets; otherwise, e.g. on 32-bit targets where `AtomicU64` is more aligned,
+    /// it is an O(n) copy.
+    ///
+    /// # Example
+    /// ```
+    /// use fastbloom::BloomFilter;
+    ///
+    /// let bloom = BloomFilter::with_num_bits(4096).items([1, 2, 3].iter());
+    /// let atomic = bloom.into_atomic();
+    /// atomic.insert(&4);
+    /// assert!([1, 2, 3, 4].iter().all(|x| atomic.contains(x)));
+    /// ```
+    pub fn into_atomic(self) -> AtomicBloomFilter<S> {
+        AtomicBloomFilter {
+            bits: self.bits.into_atomic(),
+            num_hashes_minus_one: self.num_hashes_minus_one,
+            hasher: self.hasher,
+            hash_calls: self.hash_calls,
+        }
+    }
+
+    /// Unions a snapshot of the atomic Bloom filter `other` into `self`.
+    ///
+    /// Each word of `other` is read once, so concurrent inserts into `other` may or may not be reflected.
+    ///
+    /// # Panics
+    /// Panics if the Bloom filters have a different number of bits, number of hashes or hasher (e.g. a different seed).
+    ///
+    /// # Example
+    /// ```
+    /// use fastbloom::{AtomicBloomFilter, BloomFilter};
+    ///
+    /// let mut bloom = BloomFilter::with_num_bits(4096).seed(&1).hashes(4);
+    /// let other = AtomicBloomFilter::with_num_bits(4096).seed(&1).hashes(4);
+    /// other.insert(&1);
+    /// bloom.union_atomic_snapshot(&other);
+    /// assert!(bloom.contains(&1));
+    /// ```
+    #[inline]
+    pub fn union_atomic_snapshot(&mut self, other: &AtomicBloomFilter<S>) {
+        assert_eq!(
+            self.num_bits(),
+            other.num_bits(),
+            "expected same number of bits"
+        );
+        assert_eq!(
+            self.num_hashes(),
+            other.num_hashes(),
+            "expected same number of hashes"
+        );
+        assert_eq!(
+            self.hasher_fingerprint(),
+            other.hasher_fingerprint(),
+            "expected same hasher"
+        );
+        self.bits.union_atomic(&other.bits);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S: BuildHasher> AtomicBloomFilter<S> {
+    /// Converts this Bloom filter into a [`BloomFilter`] with the same bits, number of hashes and hasher.
+    ///
+    /// No items are rehashed: the bit vector is moved over word by word, so both Bloom filters give the same membership answers.
+    /// The conversion reuses the bit vector's allocation where `u64` and [`AtomicU64`](core::sync::atomic::AtomicU64)
+    /// have the same layout, as on 64-bit targets; otherwise, e.g. on 32-bit targets where `AtomicU64` is more aligned,
+    /// it is an O(n) copy.
+    ///
+    /// # Example
+    /// ```
+    /// use fastbloom::AtomicBloomFilter;
+    ///
+    /// let atomic = AtomicBloomFilter::with_num_bits(4096).items([1, 2, 3].iter());
+    /// let mut bloom = atomic.into_bloom();
+    /// bloom.insert(&4);
+    /// assert!([1, 2, 3, 4].iter().all(|x| bloom.contains(x)));
+    /// ```
+    pub fn into_bloom(self) -> BloomFilter<S> {
+        BloomFilter {
+            bits: self.bits.into_standard(),
+            num_hashes_minus_one: self.num_hashes_minus_one,
+            hasher: self.hasher,
+            hash_calls: self.hash_calls,
+        }
+    }
+
+    /// Checks if an element is possibly in the Bloom filter, loading bits with the given memory `ordering`.
+    ///
+    /// [`contains`](Self::contains) uses [`Ordering::Relaxed`], which is enough for membership checks on their own.
+    /// A stronger ordering is only needed when `contains` must observe an insert that happened-before
+    /// through some other synchronization, e.g. an item inserted with [`insert_ordered`](Self::insert_ordered)
+    /// using [`Ordering::Release`] before publishing a flag.
+    ///
+    /// # Panics
+    /// Panics if `ordering` is [`Ordering::Release`] or [`Ordering::AcqRel`].
+    ///
+    /// # Examples
+    /// ```
+    /// use core::sync::atomic::Ordering;
+    /// use fastbloom::AtomicBloomFilter;
+    ///
+    /// let bloom = AtomicBloomFilter::with_num_bits(1024).hashes(4);
+    /// bloom.insert_ordered(&1, Ordering::Release);
+    /// assert!(bloom.contains_ordered(&1, Ordering::Acquire));
+    /// ```
+    #[inline]
+    pub fn contains_ordered(&self, val: &(impl Hash + ?Sized), ordering: Ordering) -> bool {
+        self.contains_hash_ordered(self.source_hash(val), ordering)
+    }
+
+    /// Checks if the hash of an element is possibly in the Bloom filter, loading bits with the given memory `ordering`.
+    /// See [`contains_ordered`](Self::contains_ordered).
+    ///
+    /// # Panics
+    /// Panics if `ordering` is [`Ordering::Release`] or [`Ordering::AcqRel`].
+    #[inline]
+    pub fn contains_hash_ordered(&self, hash: u64, ordering: Ordering) -> bool {
+        indexes(self.num_bits(), hash, self.num_hashes_minus_one)
+            .all(|i| self.bits.check_ordered(i, ordering))
+    }
+
+    /// Inserts an element into the Bloom filter, setting bits with the given memory `ordering`.
+    ///
+    /// [`insert`](Self::insert) uses [`Ordering::Relaxed`]. See [`contains_ordered`](Self::contains_ordered)
+    /// for when a stronger ordering is useful.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
+    /// `false` otherwise.
+    #[inline]
+    pub fn insert_ordered(&self, val: &(impl Hash + ?Sized), ordering: Ordering) -> bool {
+        self.insert_hash_ordered(self.source_hash(val), ordering)
+    }
+
+    /// Clears all of the bits in the Bloom filter in a single pass, storing each word with [`Ordering::Release`].
+    ///
+    /// Unlike [`clear`](Self::clear), which uses [`Ordering::Relaxed`], a thread that observes a cleared word with
+    /// [`Ordering::Acquire`] also observes everything that happened before the clear, e.g. for publishing a reset.
+    ///
+    /// Each word is cleared independently, so items inserted concurrently with clearing may be partially
+    /// cleared, and are not guaranteed to be contained afterwards.
+    ///
+    /// # Examples
+    /// ```
+    /// use fastbloom::AtomicBloomFilter;
+    ///
+    /// let bloom = AtomicBloomFilter::with_num_bits(1024).hashes(4);
+    /// bloom.insert(&1);
+    /// bloom.clear_release();
+    /// assert!(bloom.is_empty());
+    /// ```
+    #[inline]
+    pub fn clear_release(&self) {
+        self.bits.clear_ordered(Ordering::Release);
+    }
+
+    /// Inserts the hash of an element into the Bloom filter, setting bits with the given memory `ordering`.
+    /// See [`insert_ordered`](Self::insert_ordered).
+    #[inline]
+    pub fn insert_hash_ordered(&self, hash: u64, ordering: Ordering) -> bool {
+        let mut previously_contained = true;
+        for i in indexes(self.num_bits(), hash, self.num_hashes_minus_one) {
+            previously_contained &= self.bits.set_ordered(i, ordering);
+        }
+        previously_contained
+    }
+
+    /// Merges the non-atomic Bloom filter `other` into `self`, i.e. unions it, e.g. to combine Bloom filters built separately.
+    ///
+    /// # Panics
+    /// Panics if the Bloom filters have a different number of bits, number of hashes or hasher (e.g. a different seed).
+    ///
+    /// # Example
+    /// ```
+    /// use fastbloom::{AtomicBloomFilter, BloomFilter};
+    ///
+    /// let bloom = AtomicBloomFilter::with_num_bits(4096).seed(&1).hashes(4);
+    /// let mut other = BloomFilter::with_num_bits(4096).seed(&1).hashes(4);
+    /// other.insert(&1);
+    /// bloom.merge_standard(&other);
+    /// assert!(bloom.contains(&1));
+    /// ```
+    #[inline]
+    pub fn merge_standard(&self, other: &BloomFilter<S>) {
+        assert_eq!(
+            self.num_bits(),
+            other.num_bits(),
+            "expected same number of bits"
+        );
+        assert_eq!(
+            self.num_hashes(),
+            other.num_hashes(),
+            "expected same number of hashes"
+        );
+        assert_eq!(
+            self.hasher_fingerprint(),
+            other.hasher_fingerprint(),
+            "expected same hasher"
+        );
+        self.bits.union_standard(&other.bits);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S: BuildHasher + Clone> AtomicBloomFilter<S> {
+    /// Returns a point-in-time copy of this Bloom filter as a [`BloomFilter`] with the same bits, number of hashes and hasher.
+    ///
+    /// Each word is read once with [`Ordering::Relaxed`] while other threads may keep inserting, so the copy may include
+    /// some bits of a concurrent insert and not others. Items inserted before the snapshot started are always contained.
+    ///
+    /// # Example
+    /// ```
+    /// use fastbloom::AtomicBloomFilter;
+    ///
+    /// let atomic = AtomicBloomFilter::with_num_bits(4096).hashes(4);
+    /// atomic.insert(&1);
+    /// let snapshot = atomic.snapshot();
+    /// atomic.insert(&2);
+    /// assert!(snapshot.contains(&1));
+    /// assert!(atomic.contains(&2));
+    /// ```
+    pub fn snapshot(&self) -> BloomFilter<S> {
+        BloomFilter {
+            bits: self.bits.iter().collect(),
+            num_hashes_minus_one: self.num_hashes_minus_one,
+            hasher: self.hasher.clone(),
+            hash_calls: self.hash_calls.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<S: BuildHasher + Sync> AtomicBloomFilter<S> {
+    /// Inserts all the items in `items` into the Bloom filter in parallel, using rayon's global thread pool.
+    ///
+    /// Bits are set with atomic fetch-or, so worker threads insert without locks and only contend
+    /// when they happen to touch the same word. Bulk loads therefore scale with the number of threads,
+    /// which can be measured with `cargo bench --features rayon -- par_extend`.
+    ///
+    /// Only available with the `rayon` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use fastbloom::AtomicBloomFilter;
+    /// use rayon::prelude::*;
+    ///
+    /// let bloom = AtomicBloomFilter::with_false_pos(0.001).expected_items(100_000);
+    /// bloom.par_extend((0..100_000).into_par_iter());
+    /// assert!((0..100_000).all(|x| bloom.contains(&x)));
+    /// ```
+    pub fn par_extend<T: Hash, I: rayon::iter::IntoParallelIterator<Item = T>>(&self, items: I) {
+        use rayon::iter::ParallelIterator;
+        items.into_par_iter().for_each(|x| {
+            self.insert(&x);
+        });
+    }
+}
+
+/// The result of a Bloom filter's `union_lossy`.
+#[must_use]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnionResult {
+    /// Both Bloom filters had the same number of hashes.
+    Exact,
+    /// The Bloom filters had a different number of hashes. Items from the other Bloom filter may not be contained.
+    MismatchedHashes {
+        /// The number of hashes of the Bloom filter that was unioned into.
+        num_hashes: u32,
+        /// The number of hashes of the other Bloom filter.
+        other_num_hashes: u32,
+    },
+}
+
+/// The answer to whether an item is in a Bloom filter, as returned by a Bloom filter's `membership`.
+///
+/// Bloom filters have no false negatives but may have false positives, so a "yes" is never certain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Membership {
+    /// The item was never inserted.
+    DefinitelyAbsent,
+    /// The item may have been inserted, or this is a false positive.
+    PossiblyPresent,
+}
+
+/// An error returned when two Bloom filters cannot be merged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MergeError {
+    /// The Bloom filters have a different number of bits.
+    MismatchedBits {
+        /// The number of bits of the Bloom filter that was merged into.
+        num_bits: usize,
+        /// The number of bits of the other Bloom filter.
+        other_num_bits: usize,
+    },
+    /// The Bloom filters have a different number of hashes.
+    MismatchedHashes {
+        /// The number of hashes of the Bloom filter that was merged into.
+        num_hashes: u32,
+        /// The number of hashes of the other Bloom filter.
+        other_num_hashes: u32,
+    },
+}
+
+impl core::fmt::Display for MergeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MergeError::MismatchedBits {
+                num_bits,
+                other_num_bits,
+            } => write!(
+                f,
+                "expected same number of bits, got {num_bits} and {other_num_bits}"
+            ),
+            MergeError::MismatchedHashes {
+                num_hashes,
+                other_num_hashes,
+            } => write!(
+                f,
+                "expected same number of hashes, got {num_hashes} and {other_num_hashes}"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MergeError {}
+
+/// The result of a Bloom filter's `self_test`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelfTestResult {
+    num_checked: usize,
+    num_false_negatives: usize,
+}
+
+impl SelfTestResult {
+    /// Returns the number of items checked.
+    pub fn num_checked(&self) -> usize {
+        self.num_checked
+    }
+
+    /// Returns the number of checked items not contained in the Bloom filter.
+    pub fn num_false_negatives(&self) -> usize {
+        self.num_false_negatives
+    }
+
+    /// Returns `true` if there were no false negatives.
+    pub fn is_ok(&self) -> bool {
+        self.num_false_negatives == 0
+    }
+}
+
+/// (De)serializes `num_hashes_minus_one` as the number of hashes, so serialized filters read naturally.
+///
+/// Bloom filters are deserialized by hand to keep reading the format written by 0.17.0, which stored
+/// `num_hashes_minus_one` and nested the bits in a `{"bits": [...]}` map. Sequence-based formats such as bincode
+/// don't store field names, so 0.17.0 data in those formats reads back with one hash too many.
+#[cfg(feature = "serde")]
+mod serde_num_hashes {
+    use alloc::vec::Vec;
+    use core::{fmt, marker::PhantomData};
+    use serde::de::{Error, IgnoredAny, MapAccess, SeqAccess, Visitor};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        num_hashes_minus_one: &u32,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.serialize_u64(*num_hashes_minus_one as u64 + 1)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<u32, D::Error> {
+        match u64::deserialize(d)? {
+            0 => Err(D::Error::custom("num_hashes must be at least 1")),
+            x => u32::try_from(x - 1).map_err(D::Error::custom),
+        }
+    }
+
+    /// Deserializes a Bloom filter's bits, `num_hashes_minus_one` and hasher, in the current or the 0.17.0 format.
+    pub(crate) fn deserialize_parts<'de, D: Deserializer<'de>, S: Deserialize<'de>>(
+        d: D,
+        name: &'static str,
+    ) -> Result<(Vec<u64>, u32, S), D::Error> {
+        d.deserialize_struct(
+            name,
+            &["bits", "num_hashes", "hasher"],
+            PartsVisitor(PhantomData),
+        )
+    }
+
+    struct NumHashes(u32);
+
+    impl<'de> Deserialize<'de> for NumHashes {
+        fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            deserialize(d).map(NumHashes)
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Bits {
+        Flat(Vec<u64>),
+        /// 0.17.0 nested the bits in the bit vector's own struct.
+        Nested {
+            bits: Vec<u64>,
+        },
+    }
+
+    #[derive(Deserialize)]
+    #[serde(field_identifier, rename_all = "snake_case")]
+    enum Field {
+        // Same order as the serialized fields, for formats that identify fields by index.
+        Bits,
+        NumHashes,
+        Hasher,
+        NumHashesMinusOne,
+        #[serde(other)]
+        Other,
+    }
+
+    struct PartsVisitor<S>(PhantomData<S>);
+
+    impl<'de, S: Deserialize<'de>> Visitor<'de> for PartsVisitor<S> {
+        type Value = (Vec<u64>, u32, S);
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a Bloom filter")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let bits = seq
+                .next_element()?
+                .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+            let NumHashes(num_hashes_minus_one) = seq
+                .next_element()?
+                .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+            let hasher = seq
+                .next_element()?
+                .ok_or_else(|| A::Error::invalid_length(2, &self))?;
+            Ok((bits, num_hashes_minus_one, hasher))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let (mut bits, mut num_hashes_minus_one, mut hasher) = (None, None, None);
+            while let Some(field) = map.next_key()? {
+                match field {
+                    Field::Bits => {
+                        bits = Some(match map.next_value()? {
+                            Bits::Flat(bits) | Bits::Nested { bits } => bits,
+                        })
+                    }
+                    Field::NumHashes => {
+                        num_hashes_minus_one = Some(map.next_value::<NumHashes>()?.0)
+                    }
+                    Field::NumHashesMinusOne => num_hashes_minus_one = Some(map.next_value()?),
+                    Field::Hasher => hasher = Some(map.next_value()?),
+                    Field::Other => {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+            }
+            Ok((
+                bits.ok_or_else(|| A::Error::missing_field("bits"))?,
+                num_hashes_minus_one.ok_or_else(|| A::Error::missing_field("num_hashes"))?,
+                hasher.ok_or_else(|| A::Error::missing_field("hasher"))?,
+            ))
+        }
+    }
+}
+
+/// The false positive rate of Bloom filters built with [`FromIterator`].
+#[cfg(feature = "alloc")]
+const DEFAULT_FALSE_POS: f64 = 0.01;
+
+/// The largest supported number of bits: the largest multiple of 64 that fits in a `usize`,
+/// so that the total number of bits of the underlying `u64`s never overflows.
+#[cfg(feature = "alloc")]
+pub(crate) const MAX_NUM_BITS: usize = usize::MAX - (u64::BITS as usize - 1);
+
 /// Returns a the bit index for an item's hash.
 /// The bit index must be in the range `0..num_bits`.
 /// This implementation is a more performant alternative to `hash % num_bits`:
@@ -398,71 +1922,410 @@ pub(crate) fn index(num_bits: usize, hash: u64) -> usize {
     ((hash as u128 * num_bits as u128) >> 64) as usize
 }
 
-macro_rules! impl_tests {
-    ($modname:ident, $name:ident) => {
-        #[allow(unused_mut)]
-        #[cfg(not(feature = "loom"))]
-        #[cfg(test)]
-        mod $modname {
-            use super::*;
-            use alloc::format;
+/// Returns the bit indexes of an item with source `hash`: the first from `hash` itself,
+/// and `num_hashes_minus_one` more from a [`DoubleHasher`].
+///
+/// The double hasher is only set up once the second index is needed, so single-hash filters,
+/// and lookups that miss on the first bit, skip it.
+#[cfg(feature = "alloc")]
+#[inline]
+pub(crate) fn indexes(
+    num_bits: usize,
+    hash: u64,
+    num_hashes_minus_one: u32,
+) -> impl Iterator<Item = usize> {
+    let mut hasher = None;
+    let hashes = core::iter::from_fn(move || {
+        Some(hasher.get_or_insert_with(|| DoubleHasher::new(hash)).next())
+    });
+    core::iter::once(hash)
+        .chain(hashes.take(num_hashes_minus_one as usize))
+        .map(move |h| index(num_bits, h))
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! impl_tests {
+    ($modname:ident, $name:ident) => {
+        #[allow(unused_mut)]
+        #[cfg(not(feature = "loom"))]
+        #[cfg(test)]
+        mod $modname {
+            use super::*;
+            use alloc::{
+                format,
+                string::{String, ToString},
+                vec,
+            };
+            use core::iter::repeat;
+
+            trait Seeded: BuildHasher {
+                fn seeded(seed: &[u8; 16]) -> Self;
+            }
+            impl Seeded for DefaultHasher {
+                fn seeded(seed: &[u8; 16]) -> Self {
+                    Self::seeded(seed)
+                }
+            }
+
+            const TRIALS: usize = 20_000_000;
+
+            fn false_pos_rate<H: BuildHasher>(filter: &$name<H>) -> f64 {
+                let mut total = 0;
+                let mut false_positives = 0;
+                for x in non_member_nums() {
+                    total += 1;
+                    false_positives += filter.contains_hash(x) as usize;
+                }
+                (false_positives as f64) / (total as f64)
+            }
+
+            fn member_nums(num: usize) -> impl Iterator<Item = u64> {
+                random_numbers(num, 5)
+            }
+
+            fn non_member_nums() -> impl Iterator<Item = u64> {
+                random_numbers(TRIALS, 7)
+            }
+
+            fn random_numbers(num: usize, seed: u64) -> impl Iterator<Item = u64> {
+                let mut rng = fastrand::Rng::with_seed(seed);
+                (0..=num).map(move |_| rng.u64(..))
+            }
+
+            #[test]
+            fn test_to_from_vec() {
+                fn to_from_(size: usize) {
+                    let mut b = $name::new_builder(size).seed(&1).hashes(3);
+                    b.extend(member_nums(1000));
+                    let b2 = $name::new_from_vec(b.iter().collect())
+                        .seed(&1)
+                        .hashes(3);
+                    assert_eq!(b, b2);
+                    assert_eq!(b.num_bits(), b.bits.len() * 64);
+                    assert!(size <= b.bits.len() * 64);
+                    assert!((size + u64::BITS as usize) > b.bits.len() * 64);
+                }
+                for size in 1..=10009 {
+                    to_from_(size);
+                }
+            }
+
+            #[test]
+            fn contains_or_insert() {
+                let mut filter = $name::with_false_pos(0.001).expected_items(1000);
+                let num_seen = member_nums(1000).filter(|x| filter.contains_or_insert(x)).count();
+                assert!(num_seen < 10, "{}", num_seen);
+                for x in member_nums(1000) {
+                    assert!(filter.contains_or_insert(&x));
+                }
+            }
+
+            #[test]
+            fn random_insert_contains() {
+                let mut rng = fastrand::Rng::with_seed(517);
+                for _ in 0..200 {
+                    let num_bits = rng.usize(1..=1 << 16);
+                    let num_hashes = rng.u32(1..=16);
+                    let num_items = rng.usize(0..=num_bits / 8);
+                    let mut filter = $name::with_num_bits(num_bits).seed(&rng.u128(..)).hashes(num_hashes);
+
+                    let items: Vec<u64> = (0..num_items).map(|_| rng.u64(..)).collect();
+                    filter.extend(items.iter());
+                    assert!(items.iter().all(|x| filter.contains(x)));
+
+                    let num_trials = 10_000;
+                    let num_false_pos = (0..num_trials).filter(|_| filter.contains(&rng.u64(..))).count();
+                    let expected_fp = filter.expected_false_pos(num_items);
+                    assert!((num_false_pos as f64 / num_trials as f64) < 2.0 * expected_fp + 0.01);
+                }
+            }
+
+            #[test]
+            fn single_hash_probes_one_bit() {
+                let mut filter = $name::with_num_bits(1 << 12).hashes(1);
+                let mut expected = vec![0u64; filter.as_slice().len()];
+                for x in member_nums(1000) {
+                    let i = index(filter.num_bits(), x);
+                    assert_eq!(filter.insert_hash(x), expected[i / 64] & (1 << (i % 64)) != 0);
+                    expected[i / 64] |= 1 << (i % 64);
+                    assert_eq!(filter.iter().collect::<Vec<_>>(), expected);
+                }
+                for x in non_member_nums().take(10_000) {
+                    let i = index(filter.num_bits(), x);
+                    assert_eq!(filter.contains_hash(x), expected[i / 64] & (1 << (i % 64)) != 0);
+                }
+            }
+
+            #[test]
+            fn insert_hash_contains_hash() {
+                let mut filter = $name::with_false_pos(0.001).expected_items(1000);
+                for x in member_nums(1000) {
+                    filter.insert_hash(x);
+                    assert!(filter.contains_hash(x));
+                }
+                assert!(member_nums(1000).all(|x| filter.contains_hash(x)));
+
+                for x in non_member_nums().take(1000) {
+                    filter.insert(&x);
+                    assert!(filter.contains_hash(filter.source_hash(&x)));
+                    let h = filter.source_hash(&(x, 1));
+                    filter.insert_hash(h);
+                    assert!(filter.contains(&(x, 1)));
+                }
+            }
+
+            #[test]
+            fn insert_hashes() {
+                let mut filter = $name::with_false_pos(0.001).seed(&1).expected_items(1000);
+                let hashes: Vec<u64> = member_nums(1000).map(|x| filter.source_hash(&x)).collect();
+                filter.insert_hashes(&hashes);
+                assert!(member_nums(1000).all(|x| filter.contains(&x)));
+
+                let mut other = $name::with_num_bits(filter.num_bits()).seed(&1).hashes(filter.num_hashes());
+                other.extend(member_nums(1000));
+                assert_eq!(filter, other);
+            }
+
+            #[test]
+            fn raw_parts_round_trip() {
+                let mut filter = $name::with_false_pos(0.001).expected_items(1000);
+                filter.extend(member_nums(1000));
+                let num_bits = filter.num_bits();
+                let num_hashes = filter.num_hashes();
+                let expected: Vec<bool> = non_member_nums().take(10_000).map(|x| filter.contains(&x)).collect();
+
+                let (bit_vec, hashes, hasher) = filter.into_raw_parts();
+                assert_eq!(bit_vec.len() * 64, num_bits);
+                assert_eq!(hashes, num_hashes);
+                let filter = $name::from_raw_parts(bit_vec, hashes, hasher).unwrap();
+                assert!(member_nums(1000).all(|x| filter.contains(&x)));
+                assert_eq!(non_member_nums().take(10_000).map(|x| filter.contains(&x)).collect::<Vec<_>>(), expected);
+
+                assert_eq!(
+                    $name::from_raw_parts(vec![], 1, DefaultHasher::default()).unwrap_err(),
+                    BuildError::EmptyBitVec
+                );
+                assert_eq!(
+                    $name::from_raw_parts(vec![1], 0, DefaultHasher::default()).unwrap_err(),
+                    BuildError::ZeroHashes
+                );
+            }
+
+            #[test]
+            fn contains_many() {
+                let mut filter = $name::with_false_pos(0.01).expected_items(1000);
+                filter.extend(member_nums(1000));
+                for len in [0, 1, 7, 8, 9, 1000] {
+                    let items: Vec<u64> = member_nums(1000).zip(non_member_nums()).flat_map(|(a, b)| [a, b]).take(len).collect();
+                    let expected: Vec<bool> = items.iter().map(|x| filter.contains(x)).collect();
+                    assert_eq!(filter.contains_many(&items), expected);
+                }
+            }
+
+            #[test]
+            fn membership_matches_contains() {
+                let mut filter = $name::with_false_pos(0.01).expected_items(1000);
+                filter.extend(member_nums(1000));
+                for x in member_nums(1000) {
+                    assert_eq!(filter.membership(&x), Membership::PossiblyPresent);
+                }
+                for x in non_member_nums().take(10_000) {
+                    let expected = match filter.contains(&x) {
+                        true => Membership::PossiblyPresent,
+                        false => Membership::DefinitelyAbsent,
+                    };
+                    assert_eq!(filter.membership(&x), expected);
+                }
+            }
+
+            #[test]
+            fn as_slice() {
+                for num_bits in [64, 1024, 1 << 16] {
+                    let mut filter = $name::with_num_bits(num_bits).hashes(4);
+                    filter.extend(member_nums(100));
+                    assert_eq!(filter.as_slice().len(), filter.num_bits() / 64);
+                }
+            }
+
+            #[test]
+            fn dedup_iter() {
+                let filter = $name::with_false_pos(0.001).expected_items(1000);
+                let items: Vec<u64> = member_nums(1000).collect();
+                let stream = items.iter().chain(items.iter()).chain(items[..500].iter());
+                let unique: Vec<u64> = filter.dedup_iter(stream).copied().collect();
+                assert!(unique.len() > items.len() - 10, "{}", unique.len());
+                let expected: Vec<u64> = items.iter().copied().filter(|x| unique.contains(x)).collect();
+                assert_eq!(unique, expected);
+            }
+
+            #[test]
+            fn num_bytes() {
+                let filter = $name::with_num_bytes(4096).hashes(4);
+                assert_eq!(filter.num_bytes(), 4096);
+                assert_eq!(filter.num_bits(), 32768);
+                for (num_bytes, expected) in [(1, 8), (8, 8), (9, 16), (1000, 1000), (1001, 1008)] {
+                    assert_eq!($name::with_num_bytes(num_bytes).hashes(1).num_bytes(), expected);
+                }
+            }
+
+            #[test]
+            fn memory_usage_scales_with_bits() {
+                let overhead = core::mem::size_of::<$name>();
+                for num_bits in [64, 1024, 1 << 16, 1 << 20] {
+                    let filter = $name::with_num_bits(num_bits).hashes(4);
+                    assert_eq!(filter.memory_usage(), num_bits / 8 + overhead);
+                }
+            }
+
+            #[test]
+            fn eq_compares_full_state() {
+                let items: Vec<u64> = member_nums(100).collect();
+                let mut a = $name::with_num_bits(4096).seed(&1).items(items.iter());
+                let b = $name::with_num_bits(4096).seed(&1).items(items.iter());
+                assert_eq!(a, b);
+                a.insert(&u64::MAX);
+                assert_ne!(a, b);
+
+                let empty = $name::with_num_bits(4096).seed(&1).hashes(4);
+                assert_ne!(empty, $name::with_num_bits(4096).seed(&2).hashes(4));
+                assert_ne!(empty, $name::with_num_bits(4096).seed(&1).hashes(5));
+            }
+
+            #[test]
+            fn is_subset() {
+                let items: Vec<u64> = member_nums(1000).collect();
+                let empty = $name::with_num_bits(1 << 14).seed(&1).hashes(4);
+                let mut all = $name::with_num_bits(1 << 14).seed(&1).hashes(4);
+                let mut half = $name::with_num_bits(1 << 14).seed(&1).hashes(4);
+                let mut other_half = $name::with_num_bits(1 << 14).seed(&1).hashes(4);
+                all.extend(items.iter());
+                half.extend(items[..500].iter());
+                other_half.extend(non_member_nums().take(1));
+
+                // equal
+                assert!(all.is_subset(&all.clone()));
+                assert!(empty.is_subset(&empty.clone()));
+                // strict subset
+                assert!(empty.is_subset(&all));
+                assert!(half.is_subset(&all));
+                assert!(!all.is_subset(&half));
+                // disjoint
+                assert!(!other_half.is_subset(&half));
+                assert!(!half.is_subset(&other_half));
+            }
 
-            trait Seeded: BuildHasher {
-                fn seeded(seed: &[u8; 16]) -> Self;
+            #[test]
+            fn mask_with() {
+                let items: Vec<u64> = member_nums(2000).collect();
+                let mut bloom = $name::with_num_bits(1 << 14).seed(&1).hashes(4);
+                let mut allowed = $name::with_num_bits(1 << 14).seed(&1).hashes(4);
+                bloom.extend(items[..1000].iter());
+                allowed.extend(items[500..1500].iter());
+                bloom.mask_with(&allowed);
+
+                assert!(bloom.is_subset(&allowed));
+                assert!(items[500..1000].iter().all(|x| bloom.contains(x)));
+                for x in non_member_nums().take(10_000).chain(items[1000..].iter().copied()) {
+                    assert!(!bloom.contains(&x) || allowed.contains(&x));
+                }
             }
-            impl Seeded for DefaultHasher {
-                fn seeded(seed: &[u8; 16]) -> Self {
-                    Self::seeded(seed)
+
+            #[test]
+            #[should_panic(expected = "expected same hasher")]
+            fn is_subset_different_hasher() {
+                let a = $name::with_num_bits(1 << 14).seed(&1).hashes(4);
+                let b = $name::with_num_bits(1 << 14).seed(&2).hashes(4);
+                a.is_subset(&b);
+            }
+
+            #[test]
+            fn estimate_difference_cardinality() {
+                let items: Vec<u64> = random_numbers(2000, 7).collect();
+                for (num_bits, num_hashes) in [(1 << 14, 4), (1 << 16, 7), (1 << 18, 10)] {
+                    // A has 1000 items, B shares 600 of them
+                    let mut a = $name::with_num_bits(num_bits).seed(&1).hashes(num_hashes);
+                    let mut b = $name::with_num_bits(num_bits).seed(&1).hashes(num_hashes);
+                    a.extend(items[..1000].iter());
+                    b.extend(items[400..1400].iter());
+                    let estimate = a.estimate_difference_cardinality(&b);
+                    let err = (estimate - 400.0).abs() / 400.0;
+                    assert!(err < 0.15, "{num_bits} bits, {num_hashes} hashes: {estimate}");
+                    assert!(b.estimate_difference_cardinality(&a) > 340.0);
+                    assert_eq!(a.estimate_difference_cardinality(&a), 0.0);
                 }
             }
 
-            const TRIALS: usize = 20_000_000;
+            #[test]
+            fn extend_by_ref() {
+                let items: Vec<String> = member_nums(1000).map(|x| x.to_string()).collect();
+                let mut by_ref = $name::with_num_bits(1 << 14).seed(&1).hashes(4);
+                let mut by_value = $name::with_num_bits(1 << 14).seed(&1).hashes(4);
+                by_ref.extend(items.iter());
+                by_value.extend(items.clone());
+                assert_eq!(by_ref, by_value);
+                for x in non_member_nums().take(1000).map(|x| x.to_string()) {
+                    assert_eq!(by_ref.contains(&x), by_value.contains(&x));
+                }
+            }
 
-            fn false_pos_rate<H: BuildHasher>(filter: &$name<H>) -> f64 {
-                let mut total = 0;
-                let mut false_positives = 0;
-                for x in non_member_nums() {
-                    total += 1;
-                    false_positives += filter.contains_hash(x) as usize;
+            #[test]
+            fn intersect_assign_converges() {
+                let items: Vec<u64> = member_nums(1000).collect();
+                let mut acc = $name::with_false_pos(0.001).seed(&1).expected_items(1000);
+                acc.extend(items.iter());
+                // each shard drops another 100 items from the front
+                for start in (100..=500).step_by(100) {
+                    let mut shard = $name::with_false_pos(0.001).seed(&1).expected_items(1000);
+                    shard.extend(items[start..].iter());
+                    acc &= &shard;
+                    assert!(items[start..].iter().all(|x| acc.contains(x)));
                 }
-                (false_positives as f64) / (total as f64)
+                let dropped = items[..500].iter().filter(|x| acc.contains(x)).count();
+                assert!(dropped < 10, "{dropped}");
             }
 
-            fn member_nums(num: usize) -> impl Iterator<Item = u64> {
-                random_numbers(num, 5)
+            #[test]
+            fn collect_filters() {
+                let items: Vec<u64> = member_nums(3000).collect();
+                let filters = items.chunks(1000).map(|chunk| {
+                    let mut filter = $name::with_false_pos(0.001).seed(&1).expected_items(3000);
+                    filter.extend(chunk.iter());
+                    filter
+                });
+                let combined: $name = filters.collect();
+                assert!(items.iter().all(|x| combined.contains(x)));
             }
 
-            fn non_member_nums() -> impl Iterator<Item = u64> {
-                random_numbers(TRIALS, 7)
+            #[test]
+            #[should_panic(expected = "expected same hasher")]
+            fn collect_filters_different_hasher() {
+                let _: $name = [1, 2]
+                    .iter()
+                    .map(|seed| $name::with_num_bits(4096).seed(seed).hashes(4))
+                    .collect();
             }
 
-            fn random_numbers(num: usize, seed: u64) -> impl Iterator<Item = u64> {
-                let mut rng = fastrand::Rng::with_seed(seed);
-                (0..=num).map(move |_| rng.u64(..))
+            #[test]
+            #[should_panic(expected = "expected at least one Bloom filter")]
+            fn collect_no_filters() {
+                let _: $name = core::iter::empty::<$name>().collect();
             }
 
             #[test]
-            fn test_to_from_vec() {
-                fn to_from_(size: usize) {
-                    let mut b = $name::new_builder(size).seed(&1).hashes(3);
-                    b.extend(member_nums(1000));
-                    let b2 = $name::new_from_vec(b.iter().collect())
-                        .seed(&1)
-                        .hashes(3);
-                    assert_eq!(b, b2);
-                    assert_eq!(b.num_bits(), b.bits.len() * 64);
-                    assert!(size <= b.bits.len() * 64);
-                    assert!((size + u64::BITS as usize) > b.bits.len() * 64);
-                }
-                for size in 1..=10009 {
-                    to_from_(size);
-                }
+            fn collect() {
+                let items: Vec<u64> = member_nums(1000).collect();
+                let collected: $name = items.iter().collect();
+                let expected = $name::with_false_pos(0.01).items(items.iter());
+                assert_eq!(collected.num_bits(), expected.num_bits());
+                assert_eq!(collected.num_hashes(), expected.num_hashes());
+                assert!(items.iter().all(|x| collected.contains(x)));
             }
 
             #[test]
             fn first_insert_false() {
                 let mut filter = $name::with_num_bits(1202).expected_items(4);
                 assert!(!filter.insert(&5));
+                assert!(filter.insert(&5));
             }
 
             #[test]
@@ -532,6 +2395,319 @@ macro_rules! impl_tests {
                 }
             }
 
+            #[test]
+            fn empty_until_insert() {
+                let mut filter = $name::with_num_bits(4096).expected_items(100);
+                assert!(filter.is_empty());
+                filter.insert(&1);
+                assert!(!filter.is_empty());
+                filter.clear();
+                assert!(filter.is_empty());
+                // the last word must be checked too
+                filter.insert_hash(u64::MAX);
+                assert!(!filter.is_empty());
+            }
+
+            #[cfg(feature = "profiling")]
+            #[test]
+            fn hash_call_count() {
+                let mut filter = $name::with_num_bits(4096).hashes(4);
+                assert_eq!(filter.hash_call_count(), 0);
+                for x in 0..100 {
+                    filter.insert(&x);
+                }
+                assert_eq!(filter.hash_call_count(), 100);
+                for x in 0..100 {
+                    filter.insert_hash(x);
+                    filter.contains(&x);
+                }
+                assert_eq!(filter.hash_call_count(), 200);
+            }
+
+            #[test]
+            fn match_strength() {
+                let filter = $name::with_num_bits(1 << 16).items(member_nums(1000).collect::<Vec<_>>().iter());
+                assert!(member_nums(1000).all(|x| filter.match_strength(&x) == 1.0));
+                let scores = (0..1000u64).map(|x| filter.match_strength(&x)).collect::<Vec<_>>();
+                assert!(scores.iter().all(|s| (0.0..=1.0).contains(s)));
+                assert!(scores.iter().filter(|s| **s < 1.0).count() > 900);
+                for x in 0..1000u64 {
+                    assert_eq!(filter.match_strength(&x) == 1.0, filter.contains(&x));
+                }
+            }
+
+            #[test]
+            fn bitor_assign_fold() {
+                let shards: Vec<Vec<u64>> = (0..8).map(|i| random_numbers(100, i).collect()).collect();
+                let mut acc = $name::with_num_bits(1 << 14).seed(&1).hashes(5);
+                for shard in shards.iter() {
+                    let mut filter = $name::with_num_bits(1 << 14).seed(&1).hashes(5);
+                    filter.extend(shard.iter());
+                    acc |= &filter;
+                }
+
+                let mut expected = $name::with_num_bits(1 << 14).seed(&1).hashes(5);
+                expected.extend(shards.iter().flatten());
+                assert_eq!(acc, expected);
+            }
+
+            #[test]
+            #[should_panic(expected = "expected same hasher")]
+            fn bitor_assign_mismatched_hasher() {
+                let mut a = $name::with_num_bits(4096).seed(&1).hashes(4);
+                a |= &$name::with_num_bits(4096).seed(&2).hashes(4);
+            }
+
+            #[test]
+            fn union_counting() {
+                let mut filter = $name::with_num_bits(1 << 16).seed(&1).hashes(4);
+                filter.extend(member_nums(1000));
+
+                let mut subset = $name::with_num_bits(1 << 16).seed(&1).hashes(4);
+                subset.extend(member_nums(500));
+                assert_eq!(filter.union_counting(&subset), Ok(0));
+
+                let mut disjoint = $name::with_num_bits(1 << 16).seed(&1).hashes(4);
+                disjoint.extend(non_member_nums().take(1000));
+                let before = filter.count_ones();
+                let newly_set = filter.union_counting(&disjoint).unwrap();
+                assert!(newly_set > 3000);
+                assert_eq!(filter.count_ones(), before + newly_set);
+                assert_eq!(filter.union_counting(&disjoint), Ok(0));
+
+                let other = $name::with_num_bits(1 << 16).seed(&1).hashes(5);
+                assert_eq!(
+                    filter.union_counting(&other),
+                    Err(MergeError::MismatchedHashes {
+                        num_hashes: 4,
+                        other_num_hashes: 5
+                    })
+                );
+                let other = $name::with_num_bits(1 << 15).seed(&1).hashes(4);
+                assert_eq!(
+                    filter.union_counting(&other),
+                    Err(MergeError::MismatchedBits {
+                        num_bits: 1 << 16,
+                        other_num_bits: 1 << 15
+                    })
+                );
+                assert_eq!(filter.count_ones(), before + newly_set);
+            }
+
+            #[test]
+            fn union_lossy() {
+                let mut filter = $name::with_num_bits(1 << 16).seed(&1).hashes(4);
+                let other = $name::with_num_bits(1 << 16).seed(&1).hashes(4);
+                filter.extend(member_nums(1000));
+                assert_eq!(filter.union_lossy(&other), UnionResult::Exact);
+
+                let mut other = $name::with_num_bits(1 << 16).seed(&1).hashes(7);
+                other.extend(0..1000);
+                assert_eq!(
+                    filter.union_lossy(&other),
+                    UnionResult::MismatchedHashes {
+                        num_hashes: 4,
+                        other_num_hashes: 7
+                    }
+                );
+                assert!(member_nums(1000).all(|x| filter.contains(&x)));
+                assert_eq!(filter.num_hashes(), 4);
+            }
+
+            #[test]
+            fn unordered_composite_keys() {
+                let mut filter = $name::with_num_bits(1 << 16).hashes(4);
+                let fields = member_nums(100).map(|x| filter.source_hash(&x)).collect::<Vec<_>>();
+                for pair in fields.chunks(2) {
+                    filter.insert_unordered(pair.iter().copied());
+                }
+                for pair in fields.chunks(2) {
+                    assert!(filter.contains_unordered(pair.iter().rev().copied()));
+                }
+                assert!(filter.contains_unordered([fields[1], fields[0]]));
+                assert!(!filter.contains_unordered([fields[0], fields[2]]));
+            }
+
+            #[test]
+            fn rebuild_tightened() {
+                let fp = 0.001;
+                let items = member_nums(1000).collect::<Vec<_>>();
+                let filter = $name::with_num_bits(1 << 20).seed(&1).items(items.iter());
+                let tight = filter.rebuild_tightened(items.iter(), fp);
+                assert!(tight.num_bits() < filter.num_bits());
+                assert!(tight.num_bits() - crate::optimal_size(items.len(), fp) < 64);
+                assert!(tight.expected_false_pos(items.len()) < fp * 1.1);
+                assert!(items.iter().all(|x| tight.contains(x)));
+                assert_eq!(tight.source_hash(&1), filter.source_hash(&1));
+            }
+
+            #[test]
+            fn with_new_seed_rebuild() {
+                let items = member_nums(1000).collect::<Vec<_>>();
+                let mut filter = $name::with_num_bits(1 << 14).seed(&1).hashes(5);
+                filter.insert_all(items.iter());
+                let rebuilt = filter.with_new_seed_rebuild(items.iter(), &2);
+                assert_eq!(rebuilt.num_bits(), filter.num_bits());
+                assert_eq!(rebuilt.num_hashes(), filter.num_hashes());
+                assert!(rebuilt.iter().ne(filter.iter()));
+                assert_ne!(rebuilt.source_hash(&1), filter.source_hash(&1));
+                assert!(items.iter().all(|x| rebuilt.contains(x)));
+
+                let mut expected = $name::with_num_bits(1 << 14).seed(&2).hashes(5);
+                expected.insert_all(items.iter());
+                assert_eq!(rebuilt, expected);
+            }
+
+            #[test]
+            fn bitor() {
+                let mut a = $name::with_num_bits(1 << 16).seed(&1).hashes(4);
+                let mut b = $name::with_num_bits(1 << 16).seed(&1).hashes(4);
+                a.extend(0..1000);
+                b.extend(1000..2000);
+                let union = &a | &b;
+                assert!((0..2000).all(|x| union.contains(&x)));
+                a.union(&b);
+                assert_eq!(union, a);
+            }
+
+            #[test]
+            fn bitand() {
+                let mut a = $name::with_num_bits(1 << 16).seed(&1).hashes(4);
+                let mut b = $name::with_num_bits(1 << 16).seed(&1).hashes(4);
+                a.extend(0..1000);
+                b.extend(500..1500);
+                let intersection = &a & &b;
+                assert!((500..1000).all(|x| intersection.contains(&x)));
+                assert_eq!(intersection, a.intersection(&b));
+                a.intersect(&b);
+                assert_eq!(intersection, a);
+            }
+
+            #[test]
+            #[should_panic(expected = "expected same hasher")]
+            fn bitand_mismatched_seed() {
+                let _ = &$name::with_num_bits(64).seed(&1).hashes(4) & &$name::with_num_bits(64).seed(&2).hashes(4);
+            }
+
+            #[test]
+            #[should_panic(expected = "expected same number of bits")]
+            fn bitor_mismatched_bits() {
+                let _ = &$name::with_num_bits(64).seed(&1).hashes(4) | &$name::with_num_bits(128).seed(&1).hashes(4);
+            }
+
+            #[test]
+            #[should_panic(expected = "expected same number of hashes")]
+            fn bitor_mismatched_hashes() {
+                let _ = &$name::with_num_bits(64).seed(&1).hashes(4) | &$name::with_num_bits(64).seed(&1).hashes(3);
+            }
+
+            #[test]
+            #[should_panic(expected = "expected same hasher")]
+            fn bitor_mismatched_seed() {
+                let _ = &$name::with_num_bits(64).seed(&1).hashes(4) | &$name::with_num_bits(64).seed(&2).hashes(4);
+            }
+
+            #[test]
+            fn density() {
+                let mut filter = $name::with_num_bits(1 << 20).hashes(5);
+                assert_eq!(filter.count_ones(), 0);
+                assert_eq!(filter.density(), 0.0);
+                for num_items in [1_000, 10_000, 100_000] {
+                    filter.clear();
+                    filter.extend(member_nums(num_items - 1));
+                    let expected = crate::expected_density(5, 1 << 20, num_items);
+                    let err = (filter.density() - expected).abs() / expected;
+                    assert!(err < 0.01, "{} {}", filter.density(), expected);
+                }
+            }
+
+            #[test]
+            fn current_false_pos() {
+                let fp = 0.01;
+                let mut filter = $name::with_false_pos(fp).expected_items(10_000);
+                assert_eq!(filter.current_false_pos(), 0.0);
+                filter.extend(member_nums(10_000));
+                let err = (filter.current_false_pos() - fp).abs() / fp;
+                assert!(err < 0.1, "{}", filter.current_false_pos());
+                filter.extend(member_nums(20_000));
+                assert!(filter.current_false_pos() > 2.0 * fp);
+                assert!(filter.current_false_pos() < 1.0);
+            }
+
+            #[test]
+            fn insert_contains_ref() {
+                use alloc::{string::String, vec};
+
+                let mut filter = $name::with_num_bits(1 << 12).seed(&1).hashes(4);
+                filter.insert_ref("x");
+                filter.insert_ref(vec![1u8, 2, 3]);
+                assert!(filter.contains_ref(String::from("x")));
+                assert!(filter.contains_ref(b"x"));
+                assert!(filter.contains_ref([1u8, 2, 3]));
+                assert!(filter.contains_ref(&[1u8, 2, 3][..]));
+                assert!(!filter.contains_ref("y"));
+            }
+
+            #[test]
+            fn estimate_cardinality() {
+                for num_items in [1_000, 100_000] {
+                    let mut filter = $name::with_false_pos(0.01).expected_items(num_items);
+                    assert_eq!(filter.estimate_cardinality(), 0.0);
+                    filter.extend(member_nums(num_items - 1));
+                    let err = (filter.estimate_cardinality() - num_items as f64).abs() / num_items as f64;
+                    assert!(err < 0.1, "{}", filter.estimate_cardinality());
+                }
+
+                let mut filter = $name::with_num_bits(64).hashes(4);
+                filter.extend(member_nums(1000));
+                assert_eq!(filter.estimate_cardinality(), f64::INFINITY);
+            }
+
+            #[test]
+            fn capacity_headroom() {
+                let fp = 0.01;
+                let mut filter = $name::with_false_pos(fp).expected_items(10_000);
+                let empty = filter.capacity_headroom(fp);
+                assert!(empty.abs_diff(10_000) < 500, "{empty}");
+
+                filter.extend(member_nums(5_000));
+                let half = filter.capacity_headroom(fp);
+                assert!(half.abs_diff(5_000) < 500, "{half}");
+
+                filter.extend(member_nums(20_000));
+                assert_eq!(filter.capacity_headroom(fp), 0);
+            }
+
+            #[test]
+            fn self_test() {
+                let items = member_nums(1000).collect::<Vec<_>>();
+                let filter = $name::with_num_bits(1 << 16).seed(&1).items(items.iter());
+                let result = filter.self_test(&items);
+                assert!(result.is_ok());
+                assert_eq!(result.num_checked(), items.len());
+                assert_eq!(result.num_false_negatives(), 0);
+
+                let mismatched = $name::from_vec(filter.iter().collect())
+                    .seed(&2)
+                    .hashes(filter.num_hashes());
+                let result = mismatched.self_test(&items);
+                assert!(!result.is_ok());
+                assert!(result.num_false_negatives() > 0);
+            }
+
+            #[test]
+            fn clear_keeps_allocation_and_hasher() {
+                let mut filter = $name::with_num_bits(4096).seed(&1).hashes(4);
+                filter.extend(member_nums(100));
+                let ptr = filter.as_slice().as_ptr();
+                let hash = filter.source_hash(&1);
+                filter.clear();
+                assert!(filter.is_empty());
+                assert_eq!(filter.as_slice().as_ptr(), ptr);
+                assert_eq!(filter.source_hash(&1), hash);
+                assert_eq!(filter.num_hashes(), 4);
+            }
+
             #[test]
             fn random_inserts_always_contained() {
                 for mag in 1..6 {
@@ -674,8 +2850,8 @@ macro_rules! impl_tests {
             #[test]
             fn eq_constructors_num_bits() {
                 assert_eq!(
-                    $name::with_num_bits(4).hashes(4),
-                    $name::new_builder(4).hashes(4),
+                    $name::with_num_bits(4).seed(&1).hashes(4),
+                    $name::new_builder(4).seed(&1).hashes(4),
                 );
             }
 
@@ -732,16 +2908,324 @@ macro_rules! impl_tests {
                     }
                 }
             }
+
+            #[cfg(feature = "serde")]
+            #[test]
+            fn test_serde_json_contains() {
+                let mut before = $name::with_false_pos(0.01).expected_items(1000);
+                before.extend(member_nums(1000));
+
+                let json = serde_json::to_string(&before).unwrap();
+                let after: $name = serde_json::from_str(&json).unwrap();
+                assert!(member_nums(1000).all(|x| after.contains(&x)));
+                assert!(non_member_nums().take(100_000).all(|x| before.contains(&x) == after.contains(&x)));
+            }
+
+            #[cfg(feature = "serde")]
+            #[test]
+            fn test_serde_json_field_names() {
+                let mut before = $name::with_num_bits(128).seed(&42).hashes(3);
+                before.insert(&1);
+
+                let json = serde_json::to_value(&before).unwrap();
+                assert_eq!(json["num_hashes"], 3);
+                assert_eq!(json["bits"].as_array().unwrap().len(), 2);
+                assert!(json.get("hasher").is_some());
+
+                let after: $name = serde_json::from_value(json).unwrap();
+                assert_eq!(before, after);
+                assert!(after.contains(&1));
+
+                let mut json = serde_json::to_value(&before).unwrap();
+                json["num_hashes"] = 0.into();
+                assert!(serde_json::from_value::<$name>(json).is_err());
+            }
+
+            #[cfg(feature = "serde")]
+            #[test]
+            fn test_serde_0_17_format() {
+                // Written by 0.17.0 for `with_num_bits(128).seed(&42).hashes(3)` after inserting 1.
+                let json = r#"{"bits":{"bits":[1649267441664,36028797018963968]},"num_hashes_minus_one":2,"hasher":{"hasher":{"hasher":{"k0":0,"k1":3026418949592973312,"length":0,"state":{"v0":8317987319222330741,"v2":7816392313619706465,"v1":5651861820162731885,"v3":6801953186320246131},"tail":0,"ntail":0,"_marker":null}}}}"#;
+                let old: $name = serde_json::from_str(json).unwrap();
+                let mut expected = $name::with_num_bits(128).seed(&42).hashes(3);
+                expected.insert(&1);
+                assert_eq!(old, expected);
+                assert_eq!(old.num_hashes(), 3);
+
+                let value: serde_json::Value = serde_json::from_str(json).unwrap();
+                let cbor = serde_cbor::to_vec(&value).unwrap();
+                assert_eq!(serde_cbor::from_slice::<$name>(&cbor).unwrap(), expected);
+            }
+
+            #[cfg(feature = "serde")]
+            #[test]
+            fn test_serde_unnamed_fields() {
+                let mut before = $name::with_num_bits(128).seed(&42).hashes(3);
+                before.insert(&1);
+
+                let packed = serde_cbor::ser::to_vec_packed(&before).unwrap();
+                assert_eq!(serde_cbor::from_slice::<$name>(&packed).unwrap(), before);
+
+                let json = serde_json::to_value(&before).unwrap();
+                let array = serde_json::json!([json["bits"], json["num_hashes"], json["hasher"]]);
+                assert_eq!(serde_json::from_value::<$name>(array).unwrap(), before);
+            }
         }
     };
 }
 
+#[cfg(feature = "alloc")]
 impl_tests!(non_atomic, BloomFilter);
+#[cfg(feature = "alloc")]
 impl_tests!(atomic, AtomicBloomFilter);
 
-#[cfg(not(feature = "loom"))]
+#[cfg(feature = "alloc")]
+#[cfg(test)]
+mod index_tests {
+    use super::*;
+
+    #[test]
+    fn index_in_range_near_max() {
+        let mut rng = fastrand::Rng::with_seed(42);
+        let sizes = [
+            MAX_NUM_BITS,
+            MAX_NUM_BITS - 64,
+            u32::MAX as usize,
+            1 << (usize::BITS - 1),
+        ];
+        for num_bits in sizes {
+            for hash in [0, 1, u64::MAX, u64::MAX - 1] {
+                assert!(index(num_bits, hash) < num_bits);
+            }
+            for _ in 0..1000 {
+                assert!(index(num_bits, rng.u64(..)) < num_bits);
+            }
+        }
+    }
+
+    #[cfg(target_pointer_width = "32")]
+    #[test]
+    fn index_in_range_32_bit() {
+        assert_eq!(MAX_NUM_BITS, u32::MAX as usize - 63);
+        assert_eq!(index(MAX_NUM_BITS, u64::MAX), MAX_NUM_BITS - 1);
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn index_in_range_64_bit() {
+        assert_eq!(MAX_NUM_BITS, u64::MAX as usize - 63);
+        assert_eq!(index(MAX_NUM_BITS, u64::MAX), MAX_NUM_BITS - 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "num_bits must be at most")]
+    fn too_many_bits() {
+        let _ = BloomFilter::with_num_bits(usize::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "num_bits must be at most")]
+    fn too_many_bits_atomic() {
+        let _ = AtomicBloomFilter::with_num_bits(MAX_NUM_BITS + 1);
+    }
+}
+
+#[cfg(all(feature = "rayon", not(feature = "loom")))]
+#[cfg(test)]
+mod rayon_tests {
+    use super::*;
+    use rayon::prelude::*;
+
+    #[test]
+    fn par_extend() {
+        let items: Vec<u64> = (0..200_000).map(|x| x * 7919).collect();
+        let filter = AtomicBloomFilter::with_false_pos(0.001)
+            .seed(&1)
+            .expected_items(items.len());
+        filter.par_extend(items.par_iter());
+        assert!(items.iter().all(|x| filter.contains(x)));
+
+        let sequential = AtomicBloomFilter::with_false_pos(0.001)
+            .seed(&1)
+            .expected_items(items.len());
+        sequential.insert_all(items.iter());
+        assert_eq!(filter, sequential);
+    }
+}
+
+#[cfg(all(feature = "std", not(feature = "loom")))]
+#[cfg(test)]
+mod atomic_ordering_tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+
+    #[test]
+    fn release_acquire_publish() {
+        for x in 0..100u64 {
+            let filter = AtomicBloomFilter::with_num_bits(1 << 12).hashes(4);
+            let published = AtomicBool::new(false);
+            std::thread::scope(|s| {
+                s.spawn(|| {
+                    filter.insert_ordered(&x, Ordering::Release);
+                    published.store(true, Ordering::Release);
+                });
+                s.spawn(|| {
+                    while !published.load(Ordering::Acquire) {
+                        std::hint::spin_loop();
+                    }
+                    assert!(filter.contains_ordered(&x, Ordering::Acquire));
+                });
+            });
+        }
+    }
+
+    #[test]
+    fn clear_zeroes_all_words() {
+        for clear in [AtomicBloomFilter::clear, AtomicBloomFilter::clear_release] {
+            let filter = AtomicBloomFilter::with_num_bits(1 << 12).hashes(4);
+            for x in 0..1000 {
+                filter.insert(&x);
+            }
+            clear(&filter);
+            assert!(filter.iter().all(|x| x == 0));
+            assert!((0..1000).all(|x| !filter.contains(&x)));
+        }
+    }
+
+    #[test]
+    fn ordered_matches_relaxed() {
+        for num_hashes in [1, 4] {
+            let filter = AtomicBloomFilter::with_num_bits(1 << 12)
+                .seed(&1)
+                .hashes(num_hashes);
+            let other = AtomicBloomFilter::with_num_bits(1 << 12)
+                .seed(&1)
+                .hashes(num_hashes);
+            for x in 0..100 {
+                assert_eq!(
+                    filter.insert(&x),
+                    other.insert_ordered(&x, Ordering::SeqCst)
+                );
+            }
+            assert_eq!(filter, other);
+            for x in 0..1000 {
+                assert_eq!(
+                    filter.contains(&x),
+                    other.contains_ordered(&x, Ordering::SeqCst)
+                );
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "alloc", not(feature = "loom")))]
 #[cfg(test)]
 mod atomic_parity_tests {
+    #[test]
+    fn union_across_types() {
+        use super::*;
+
+        let mut non = BloomFilter::with_num_bits(4096).seed(&1).hashes(4);
+        let mut atomic = AtomicBloomFilter::with_num_bits(4096).seed(&1).hashes(4);
+        non.extend(0..100);
+        atomic.extend(100..200);
+
+        atomic.merge_standard(&non);
+        assert!((0..200).all(|x| atomic.contains(&x)));
+
+        let mut fresh = BloomFilter::with_num_bits(4096).seed(&1).hashes(4);
+        fresh.union_atomic_snapshot(&atomic);
+        assert!((0..200).all(|x| fresh.contains(&x)));
+        assert_eq!(
+            fresh.iter().collect::<Vec<_>>(),
+            atomic.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "expected same hasher")]
+    fn merge_standard_mismatched_seed() {
+        use super::*;
+
+        let atomic = AtomicBloomFilter::with_num_bits(4096).seed(&1).hashes(4);
+        atomic.merge_standard(&BloomFilter::with_num_bits(4096).seed(&2).hashes(4));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected same hasher")]
+    fn union_atomic_snapshot_mismatched_seed() {
+        use super::*;
+
+        let mut bloom = BloomFilter::with_num_bits(4096).seed(&1).hashes(4);
+        bloom.union_atomic_snapshot(&AtomicBloomFilter::with_num_bits(4096).seed(&2).hashes(4));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected same number of bits")]
+    fn merge_standard_mismatched_bits() {
+        use super::*;
+
+        let atomic = AtomicBloomFilter::with_num_bits(4096).seed(&1).hashes(4);
+        atomic.merge_standard(&BloomFilter::with_num_bits(8192).seed(&1).hashes(4));
+    }
+
+    #[test]
+    fn snapshot() {
+        use super::*;
+
+        let atomic = AtomicBloomFilter::with_num_bits(4096).seed(&1).hashes(4);
+        for x in 0..500 {
+            atomic.insert(&x);
+        }
+        let snapshot = atomic.snapshot();
+        assert!((0..500).all(|x| snapshot.contains(&x)));
+        assert_eq!(
+            snapshot.iter().collect::<Vec<_>>(),
+            atomic.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(snapshot.num_hashes(), atomic.num_hashes());
+        assert_eq!(snapshot.source_hash(&1), atomic.source_hash(&1));
+
+        atomic.clear();
+        assert!((0..500).all(|x| snapshot.contains(&x)));
+    }
+
+    #[test]
+    fn into_atomic_round_trip() {
+        use super::*;
+
+        let mut non = BloomFilter::with_num_bits(4096).seed(&1).hashes(4);
+        non.extend(0..500);
+        let expected: Vec<bool> = (0..2000).map(|x| non.contains(&x)).collect();
+
+        let atomic = non.into_atomic();
+        assert_eq!(atomic.num_hashes(), 4);
+        assert_eq!(
+            (0..2000).map(|x| atomic.contains(&x)).collect::<Vec<_>>(),
+            expected
+        );
+
+        let non = atomic.into_bloom();
+        assert_eq!(non.num_hashes(), 4);
+        assert_eq!(
+            (0..2000).map(|x| non.contains(&x)).collect::<Vec<_>>(),
+            expected
+        );
+        let mut rebuilt = BloomFilter::with_num_bits(4096).seed(&1).hashes(4);
+        rebuilt.extend(0..500);
+        assert_eq!(non, rebuilt);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected same number of hashes")]
+    fn union_across_types_mismatched_hashes() {
+        use super::*;
+
+        let mut non = BloomFilter::with_num_bits(4096).hashes(4);
+        let atomic = AtomicBloomFilter::with_num_bits(4096).hashes(3);
+        non.union_atomic_snapshot(&atomic);
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn serde_parity() {
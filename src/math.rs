@@ -1,3 +1,179 @@
+//! Functions for sizing Bloom filters, i.e. choosing the number of bits and hashes for a target false positive rate.
+//!
+//! These are the same calculations the builders use, and are also re-exported at the crate root.
+//!
+//! # Examples
+//! Size a Bloom filter for 10,000 items at a 1% false positive rate, and check the expected result:
+//! ```
+//! use fastbloom::math::{expected_density, expected_false_pos, optimal_hashes, optimal_size};
+//!
+//! let num_items = 10_000;
+//! let num_bits = optimal_size(num_items, 0.01);
+//! let num_hashes = optimal_hashes(num_bits, num_items);
+//! assert_eq!(num_hashes, 7);
+//!
+//! let density = expected_density(num_hashes, num_bits, num_items);
+//! assert!((density - 0.5).abs() < 0.05);
+//! let fp = expected_false_pos(num_hashes, density);
+//! assert!((fp - 0.01).abs() < 0.001);
+//! ```
+//! The resulting parameters can then be used to build the Bloom filter directly:
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! use fastbloom::{math, BloomFilter};
+//!
+//! let num_bits = math::optimal_size(10_000, 0.01);
+//! let bloom = BloomFilter::with_num_bits(num_bits).hashes(math::optimal_hashes(num_bits, 10_000));
+//! # }
+//! ```
+
+use core::{cmp::max, f64::consts::LN_2};
+
+/// Returns the optimal (for false positive rate) number of hashes to perform for an item given the expected number of items in the bloom filter.
+pub fn optimal_hashes(num_bits: usize, num_items: usize) -> u32 {
+    // Proof: <https://gopiandcode.uk/logs/log-bloomfilters-debunked.html>.
+    let num_bits = num_bits as f64;
+    let hashes = LN_2 * num_bits / num_items as f64;
+    max(round(hashes) as u32, 1)
+}
+
+/// The maximum number of hashes chosen by the builders when optimizing for the expected number of items.
+pub(crate) const DEFAULT_MAX_HASHES: u32 = 64;
+
+/// Returns the result of [`optimal_hashes`], but at most `max_hashes`.
+///
+/// When `num_items` is tiny relative to `num_bits`, the optimal number of hashes can be in the thousands.
+/// Such extreme hash counts rarely help: the false positive rate is already negligible, while every
+/// insert and lookup pays for each hash.
+pub fn optimal_hashes_capped(num_bits: usize, num_items: usize, max_hashes: u32) -> u32 {
+    optimal_hashes(num_bits, num_items).min(max_hashes)
+}
+
+/// Returns the smallest size in bits of a Bloom filter containing `num_items` items to achieve the target false positive rate.
+///
+/// The computation is done in `f64`, so it does not overflow: if the size does not fit in a `usize`
+/// (e.g. on 32-bit targets), `usize::MAX` is returned.
+pub fn optimal_size(num_items: usize, fp: f64) -> usize {
+    let result = raw_optimal_size(num_items, fp) as usize;
+    max(result, 64)
+}
+
+/// Returns the size in bits from [`optimal_size`] before it is converted to `usize` and floored at 64.
+pub(crate) fn raw_optimal_size(num_items: usize, fp: f64) -> f64 {
+    let num_items = num_items as f64;
+    let log2_2 = LN_2 * LN_2;
+    8.0 * ceil(num_items * ln(fp) / (-8.0 * log2_2))
+}
+
+/// Returns the exact number of bits needed for a Bloom filter containing `num_items` items to achieve the target false positive rate.
+///
+/// Unlike [`optimal_size`], the result is not rounded up to a multiple of 8 or floored at 64, so it can be rounded
+/// to whatever granularity a custom bit-packed storage needs. [`optimal_size`] is this value rounded up to a multiple of 8,
+/// and at least 64. Like [`optimal_size`], the result saturates at `usize::MAX`.
+///
+/// # Examples
+/// ```
+/// use fastbloom::{optimal_size, optimal_size_bits};
+///
+/// assert_eq!(optimal_size_bits(100, 0.01), 959);
+/// assert_eq!(optimal_size(100, 0.01), 960);
+/// ```
+pub fn optimal_size_bits(num_items: usize, fp: f64) -> usize {
+    let num_items = num_items as f64;
+    let log2_2 = LN_2 * LN_2;
+    ceil(num_items * ln(fp) / -log2_2) as usize
+}
+
+/// Returns the probability of a "1" bit in the Bloom filter.
+pub fn expected_density(hashes: u32, bits: usize, items: usize) -> f64 {
+    // Multiply as floats: `items * hashes` can overflow `usize`, especially on 32-bit targets.
+    let total_sets = items as f64 * hashes as f64;
+    let bits = bits as f64;
+    let prob_set = 1.0 / bits;
+    let prob_not_set = 1.0 - prob_set;
+    let prob_all_not_set = pow(prob_not_set, total_sets);
+    1.0 - prob_all_not_set
+}
+
+/// Returns the expected false positive rate of a Bloom filter.
+pub fn expected_false_pos(hashes: u32, density: f64) -> f64 {
+    pow(density, hashes as f64)
+}
+
+/// Returns the lowest false positive rate attainable by a Bloom filter of `num_bits` bits containing `num_items` items,
+/// i.e. the expected false positive rate of a Bloom filter built with `with_num_bits(num_bits).expected_items(num_items)`.
+///
+/// This can be used to check whether a target false positive rate is feasible for a given memory budget.
+///
+/// # Examples
+/// ```
+/// use fastbloom::achievable_fp;
+///
+/// assert!(achievable_fp(1 << 16, 1000) < 0.001);
+/// ```
+pub fn achievable_fp(num_bits: usize, num_items: usize) -> f64 {
+    let hashes = optimal_hashes_capped(num_bits, num_items, DEFAULT_MAX_HASHES);
+    let density = expected_density(hashes, num_bits, num_items);
+    expected_false_pos(hashes, density)
+}
+
+/// The number of bits and hashes of a Bloom filter, as returned by [`plan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilterParams {
+    /// The number of bits, a multiple of 64.
+    pub num_bits: usize,
+    /// The number of bytes of memory used by the bits, i.e. `num_bits / 8`.
+    pub num_bytes: usize,
+    /// The number of hashes per item.
+    pub num_hashes: u32,
+}
+
+/// Returns the number of bits and hashes of a Bloom filter built with `with_false_pos(fp).expected_items(expected_items)`,
+/// without allocating it.
+///
+/// This can be used to check the memory a Bloom filter would need before building it.
+/// As with the builder, `expected_items` is treated as 1 if 0 is specified.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use fastbloom::{plan, BloomFilter};
+///
+/// let params = plan(1_000_000, 0.001);
+/// assert!(params.num_bytes < 2 * 1024 * 1024);
+/// let bloom = BloomFilter::with_num_bits(params.num_bits).hashes(params.num_hashes);
+/// assert_eq!(bloom.num_bytes(), params.num_bytes);
+/// # }
+/// ```
+pub fn plan(expected_items: usize, fp: f64) -> FilterParams {
+    let expected_items = max(1, expected_items);
+    let size = optimal_size(expected_items, fp);
+    let num_bits = (size / 64 + (size % 64 != 0) as usize).saturating_mul(64);
+    FilterParams {
+        num_bits,
+        num_bytes: num_bits / 8,
+        num_hashes: optimal_hashes_capped(num_bits, expected_items, DEFAULT_MAX_HASHES),
+    }
+}
+
+/// Returns the block size in bits that fits one CPU cache line of `target_cache_line_bytes` bytes,
+/// e.g. 512 bits for the common 64-byte cache line.
+///
+/// The result is rounded down to a power of two, so that bit offsets within a block can be taken from a hash's top bits,
+/// and is at least 64 bits, one word. `BlockedBloomFilter` uses 512-bit blocks.
+///
+/// # Examples
+/// ```
+/// use fastbloom::math::recommend_block_bits;
+///
+/// assert_eq!(recommend_block_bits(64), 512);
+/// assert_eq!(recommend_block_bits(128), 1024);
+/// ```
+pub fn recommend_block_bits(target_cache_line_bytes: usize) -> usize {
+    let bits = target_cache_line_bytes.saturating_mul(8).max(64);
+    1 << (usize::BITS - 1 - bits.leading_zeros())
+}
+
 #[cfg(feature = "std")]
 #[inline]
 pub(crate) fn ln(x: f64) -> f64 {
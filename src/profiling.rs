@@ -0,0 +1,34 @@
+#[cfg(feature = "profiling")]
+use portable_atomic::{AtomicU64, Ordering::Relaxed};
+
+/// Counts calls to `build_hasher` made by a Bloom filter.
+///
+/// Without the `profiling` feature this is zero-sized and all operations are no-ops.
+#[derive(Debug, Default)]
+pub(crate) struct HashCallCounter {
+    #[cfg(feature = "profiling")]
+    count: AtomicU64,
+}
+
+impl HashCallCounter {
+    #[inline(always)]
+    pub(crate) fn increment(&self) {
+        #[cfg(feature = "profiling")]
+        self.count.fetch_add(1, Relaxed);
+    }
+
+    #[cfg(feature = "profiling")]
+    #[inline]
+    pub(crate) fn get(&self) -> u64 {
+        self.count.load(Relaxed)
+    }
+}
+
+impl Clone for HashCallCounter {
+    fn clone(&self) -> Self {
+        Self {
+            #[cfg(feature = "profiling")]
+            count: AtomicU64::new(self.get()),
+        }
+    }
+}
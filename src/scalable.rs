@@ -0,0 +1,310 @@
+use crate::{BloomFilter, BuildHasher, DefaultHasher};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+
+/// The number of items the first Bloom filter is sized for by [`ScalableBloomFilter::new`].
+const DEFAULT_INITIAL_CAPACITY: usize = 1024;
+/// Each new Bloom filter is sized for this many times the items of the previous one.
+const GROWTH: usize = 2;
+/// Each new Bloom filter's false positive rate is this many times the previous one's.
+const TIGHTENING: f64 = 0.5;
+
+/// A Bloom filter that grows as items are inserted, keeping its false positive rate below a target.
+///
+/// A [`ScalableBloomFilter`] starts as a single small [`BloomFilter`]. Once it holds as many items as it was sized for,
+/// a new [`BloomFilter`] with twice the capacity is added and receives all further inserts. Each new Bloom filter has
+/// half the false positive rate of the previous one, so the total false positive rate, which is at most the sum of
+/// the individual rates, converges to the target no matter how many Bloom filters are added.
+/// This follows "Scalable Bloom Filters" by Almeida et al. (2007).
+///
+/// [`contains`](Self::contains) checks every Bloom filter, so lookups slow down as more are added.
+/// Items are hashed only once, since all Bloom filters share the same hasher.
+///
+/// # Examples
+/// ```
+/// use fastbloom::ScalableBloomFilter;
+///
+/// let mut filter = ScalableBloomFilter::with_initial_capacity(0.001, 100);
+/// for x in 0..1000 {
+///     filter.insert(&x);
+/// }
+/// assert!((0..1000).all(|x| filter.contains(&x)));
+/// assert!(filter.num_filters() > 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScalableBloomFilter<S = DefaultHasher> {
+    filters: Vec<BloomFilter<S>>,
+    /// The number of items inserted into the last Bloom filter.
+    last_len: usize,
+    /// The number of items the last Bloom filter was sized for.
+    last_capacity: usize,
+    len: usize,
+    fp: f64,
+    hasher: S,
+}
+
+impl ScalableBloomFilter {
+    /// Creates an empty [`ScalableBloomFilter`] whose false positive rate stays below `fp`, starting with capacity for 1024 items.
+    ///
+    /// # Panics
+    /// Panics if the false positive rate, `fp`, is not between 0 and 1.
+    pub fn new(fp: f64) -> Self {
+        Self::with_initial_capacity(fp, DEFAULT_INITIAL_CAPACITY)
+    }
+
+    /// Creates an empty [`ScalableBloomFilter`] whose false positive rate stays below `fp`,
+    /// starting with capacity for `initial_capacity` items.
+    ///
+    /// # Panics
+    /// Panics if the false positive rate, `fp`, is not between 0 and 1.
+    pub fn with_initial_capacity(fp: f64, initial_capacity: usize) -> Self {
+        Self::with_hasher(fp, initial_capacity, DefaultHasher::default())
+    }
+}
+
+impl<S: BuildHasher + Clone> ScalableBloomFilter<S> {
+    /// Creates an empty [`ScalableBloomFilter`] whose false positive rate stays below `fp`,
+    /// starting with capacity for `initial_capacity` items, using `hasher` for all of its Bloom filters.
+    ///
+    /// # Panics
+    /// Panics if the false positive rate, `fp`, is not between 0 and 1.
+    pub fn with_hasher(fp: f64, initial_capacity: usize, hasher: S) -> Self {
+        assert!(fp > 0.0 && fp < 1.0, "fp must be between 0 and 1");
+        let mut filter = Self {
+            filters: Vec::new(),
+            last_len: 0,
+            last_capacity: initial_capacity.max(1),
+            len: 0,
+            fp,
+            hasher,
+        };
+        filter.grow();
+        filter
+    }
+
+    /// Adds a new Bloom filter with twice the capacity and half the false positive rate of the last one.
+    /// The first Bloom filter gets the initial capacity.
+    fn grow(&mut self) {
+        let capacity = match self.filters.is_empty() {
+            true => self.last_capacity,
+            false => self.last_capacity * GROWTH,
+        };
+        // The first Bloom filter gets `fp * (1 - TIGHTENING)`, so that the sum over all Bloom filters is `fp`.
+        let fp =
+            self.fp * (1.0 - TIGHTENING) * crate::math::pow(TIGHTENING, self.filters.len() as f64);
+        let filter = BloomFilter::with_false_pos(fp)
+            .hasher(self.hasher.clone())
+            .expected_items(capacity);
+        self.filters.push(filter);
+        self.last_len = 0;
+        self.last_capacity = capacity;
+    }
+
+    /// Inserts an element into the Bloom filter, adding a new Bloom filter first if the last one is full.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
+    /// `false` otherwise. Items that may have been present are not inserted again.
+    pub fn insert(&mut self, val: &(impl Hash + ?Sized)) -> bool {
+        let hash = self.source_hash(val);
+        if self.filters.iter().any(|f| f.contains_hash(hash)) {
+            return true;
+        }
+        if self.last_len >= self.last_capacity {
+            self.grow();
+        }
+        if let Some(last) = self.filters.last_mut() {
+            last.insert_hash(hash);
+        }
+        self.last_len += 1;
+        self.len += 1;
+        false
+    }
+
+    /// Checks if an element is possibly in any of the Bloom filters.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
+    pub fn contains(&self, val: &(impl Hash + ?Sized)) -> bool {
+        let hash = self.source_hash(val);
+        self.filters.iter().any(|f| f.contains_hash(hash))
+    }
+
+    /// Unions `other` into `self`, so that `self` contains the items of both.
+    ///
+    /// Both [`ScalableBloomFilter`]s must have been created with the same false positive rate, initial capacity and
+    /// hasher, so that their `i`-th Bloom filters have the same size. Bloom filters present in both are unioned one by one,
+    /// and the Bloom filters `other` grew beyond `self` are appended. A unioned Bloom filter may hold more items than it was
+    /// sized for, so the false positive rate may exceed the target until more Bloom filters are added.
+    /// [`len`](Self::len) becomes the sum of both lengths, counting items present in both twice.
+    ///
+    /// # Panics
+    /// Panics if the false positive rates differ, or if any pair of Bloom filters has a different number of bits,
+    /// number of hashes or hasher. `self` is left unchanged in that case.
+    ///
+    /// # Example
+    /// ```
+    /// use fastbloom::{DefaultHasher, ScalableBloomFilter};
+    ///
+    /// let hasher = DefaultHasher::seeded(&[1; 16]);
+    /// let mut a = ScalableBloomFilter::with_hasher(0.01, 100, hasher.clone());
+    /// let mut b = ScalableBloomFilter::with_hasher(0.01, 100, hasher);
+    /// a.extend(0..50);
+    /// b.extend(50..1000);
+    /// a.union(&b);
+    /// assert!((0..1000).all(|x| a.contains(&x)));
+    /// ```
+    pub fn union(&mut self, other: &Self) {
+        assert_eq!(self.fp, other.fp, "expected same false positive rate");
+        for (filter, other_filter) in self.filters.iter().zip(&other.filters) {
+            filter.assert_compatible(other_filter);
+        }
+        for (filter, other_filter) in self.filters.iter_mut().zip(&other.filters) {
+            filter.union(other_filter);
+        }
+        match other.filters.len().cmp(&self.filters.len()) {
+            Ordering::Greater => {
+                self.filters
+                    .extend_from_slice(&other.filters[self.filters.len()..]);
+                self.last_len = other.last_len;
+                self.last_capacity = other.last_capacity;
+            }
+            Ordering::Equal => self.last_len += other.last_len,
+            Ordering::Less => {}
+        }
+        self.len += other.len;
+    }
+
+    /// Returns the number of items inserted, not counting items that were possibly already present.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no items have been inserted.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of underlying Bloom filters.
+    #[inline]
+    pub fn num_filters(&self) -> usize {
+        self.filters.len()
+    }
+
+    /// Returns the total number of in-memory bits of all underlying Bloom filters.
+    pub fn num_bits(&self) -> usize {
+        self.filters.iter().map(|f| f.num_bits()).sum()
+    }
+
+    /// Returns the target false positive rate.
+    #[inline]
+    pub fn false_pos(&self) -> f64 {
+        self.fp
+    }
+
+    #[inline]
+    fn source_hash(&self, val: &(impl Hash + ?Sized)) -> u64 {
+        let mut state = self.hasher.build_hasher();
+        val.hash(&mut state);
+        state.finish()
+    }
+}
+
+impl<T: Hash, S: BuildHasher + Clone> Extend<T> for ScalableBloomFilter<S> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.insert(&val);
+        }
+    }
+}
+
+#[cfg(not(feature = "loom"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grows_within_fp() {
+        for fp in [0.1, 0.01, 0.001] {
+            let mut filter =
+                ScalableBloomFilter::with_hasher(fp, 100, DefaultHasher::seeded(&[1; 16]));
+            let num_items = 100_000u64;
+            filter.extend(0..num_items);
+            assert!((0..num_items).all(|x| filter.contains(&x)));
+            assert!(filter.num_filters() > 5);
+            assert!(filter.len() as u64 <= num_items);
+            assert!(filter.len() as f64 > num_items as f64 * (1.0 - fp));
+
+            let trials = 100_000;
+            let false_positives = (num_items..num_items + trials)
+                .filter(|x| filter.contains(x))
+                .count();
+            let sample_fp = false_positives as f64 / trials as f64;
+            // Allow for sampling noise: at fp = 0.001 only about 100 false positives are expected.
+            assert!(sample_fp < fp * 1.2, "sample fp: {sample_fp}, target: {fp}");
+        }
+    }
+
+    #[test]
+    fn starts_with_one_filter() {
+        let mut filter = ScalableBloomFilter::with_initial_capacity(0.01, 10);
+        assert!(filter.is_empty());
+        assert_eq!(filter.num_filters(), 1);
+        filter.extend(0..10);
+        assert_eq!(filter.num_filters(), 1);
+        assert_eq!(filter.len(), 10);
+        filter.insert(&10);
+        assert_eq!(filter.num_filters(), 2);
+        assert!(filter.insert(&10));
+        assert_eq!(filter.len(), 11);
+    }
+
+    #[test]
+    fn union_different_sizes() {
+        let new = || ScalableBloomFilter::with_hasher(0.01, 100, DefaultHasher::seeded(&[1; 16]));
+        let mut small = new();
+        small.extend(0..150u64);
+        let mut large = new();
+        large.extend(1000..10_000u64);
+        assert!(small.num_filters() < large.num_filters());
+
+        let mut a = small.clone();
+        a.union(&large);
+        let mut b = large.clone();
+        b.union(&small);
+        for filter in [a, b] {
+            assert_eq!(filter.num_filters(), large.num_filters());
+            assert_eq!(filter.len(), small.len() + large.len());
+            assert!((0..150u64).chain(1000..10_000).all(|x| filter.contains(&x)));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "expected same hasher")]
+    fn union_different_hashers() {
+        let mut a = ScalableBloomFilter::with_hasher(0.01, 100, DefaultHasher::seeded(&[1; 16]));
+        let b = ScalableBloomFilter::with_hasher(0.01, 100, DefaultHasher::seeded(&[2; 16]));
+        a.union(&b);
+    }
+
+    #[test]
+    fn odd_initial_capacity() {
+        let mut filter = ScalableBloomFilter::with_initial_capacity(0.01, 11);
+        filter.extend(0..11);
+        assert_eq!(filter.num_filters(), 1);
+        filter.insert(&11);
+        assert_eq!(filter.num_filters(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "fp must be between 0 and 1")]
+    fn invalid_fp() {
+        let _ = ScalableBloomFilter::new(0.0);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{index, BuildHasher, BuilderWithBits, DefaultHasher, DoubleHasher};
+use alloc::{boxed::Box, vec};
+use core::hash::{Hash, Hasher};
+
+/// A Bloom filter for unbounded streams that forgets old items to keep its false positive rate stable.
+///
+/// A [`BloomFilter`](crate::BloomFilter) fills up as items are inserted, so on an endless stream its false positive rate approaches 1.
+/// A [`StableBloomFilter`] instead keeps a small counter per cell: each insert first decrements a few cells, then sets
+/// the item's cells to the maximum count. Old items fade out as their cells decay, so the fraction of non-zero cells,
+/// and with it the false positive rate, converges to a constant no matter how many items are inserted.
+/// In exchange, items that were inserted long ago may no longer be contained, i.e. false negatives are possible.
+/// This follows "Approximately Detecting Duplicates for Streaming Data using Stable Bloom Filters" by Deng and Rafiei (2006).
+///
+/// The number of cells decremented per insert is chosen so that the false positive rate converges to the target rate.
+/// Decremented cells are consecutive, starting at a pseudo-random cell, which is faster than picking each one at random.
+///
+/// A [`StableBloomFilter`] is built with [`BloomFilter`](crate::BloomFilter)'s builders, ending in [`stable_hashes`](BuilderWithBits::stable_hashes).
+///
+/// # Examples
+/// ```
+/// use fastbloom::BloomFilter;
+///
+/// let mut filter = BloomFilter::with_num_bits(1 << 16).stable_hashes(3, 3, 0.01);
+/// for x in 0..1_000_000 {
+///     filter.insert(&x);
+/// }
+/// assert!(filter.contains(&999_999));
+/// ```
+#[derive(Debug, Clone)]
+pub struct StableBloomFilter<S = DefaultHasher> {
+    cells: Box<[u8]>,
+    num_hashes: u32,
+    max: u8,
+    /// The number of cells decremented per insert.
+    decay: usize,
+    fp: f64,
+    /// State of the generator picking the first decremented cell.
+    rng: u64,
+    hasher: S,
+}
+
+impl<S: BuildHasher> BuilderWithBits<S> {
+    /// "Consumes" this builder, returning an empty [`StableBloomFilter`] with a cell per bit, `num_hashes` hashes
+    /// per item and cells that count up to `max`, whose false positive rate converges to `fp`.
+    ///
+    /// A larger `max` keeps items for longer, at the cost of decrementing more cells per insert.
+    ///
+    /// Note: if `num_hashes` is 0, it is treated as 1.
+    ///
+    /// # Panics
+    /// Panics if `max` is 0 or the false positive rate, `fp`, is not between 0 and 1.
+    ///
+    /// # Examples
+    /// ```
+    /// use fastbloom::BloomFilter;
+    ///
+    /// let filter = BloomFilter::with_num_bits(1 << 16).seed(&1).stable_hashes(3, 3, 0.01);
+    /// assert_eq!(filter.num_cells(), 1 << 16);
+    /// ```
+    pub fn stable_hashes(self, num_hashes: u32, max: u8, fp: f64) -> StableBloomFilter<S> {
+        assert!(max > 0, "max must be greater than 0");
+        assert!(fp > 0.0 && fp < 1.0, "fp must be between 0 and 1");
+        let num_cells = self.num_bits();
+        let num_hashes = num_hashes.max(1);
+        let mut filter = StableBloomFilter {
+            cells: vec![0; num_cells].into(),
+            num_hashes,
+            max,
+            decay: decay(num_cells, num_hashes, max, fp),
+            fp,
+            rng: 0,
+            hasher: self.hasher,
+        };
+        filter.rng = filter.source_hash(&0u64);
+        filter
+    }
+}
+
+impl<S: BuildHasher> StableBloomFilter<S> {
+    /// Inserts an element into the Bloom filter, first decrementing [`decay_count`](Self::decay_count) cells.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item may have been previously in the Bloom filter (indicating a potential false positive),
+    /// `false` otherwise. The item is checked before any cells are decremented.
+    #[inline]
+    pub fn insert(&mut self, val: &(impl Hash + ?Sized)) -> bool {
+        let hash = self.source_hash(val);
+        let previously_contained = self.contains_hash(hash);
+        self.decrement();
+        for i in self.indices(hash) {
+            self.cells[i] = self.max;
+        }
+        previously_contained
+    }
+
+    /// Checks if an element is possibly in the Bloom filter.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item is possibly in the Bloom filter, `false` otherwise.
+    /// Items inserted long ago may have decayed and are then no longer contained.
+    #[inline]
+    pub fn contains(&self, val: &(impl Hash + ?Sized)) -> bool {
+        self.contains_hash(self.source_hash(val))
+    }
+
+    #[inline]
+    fn contains_hash(&self, hash: u64) -> bool {
+        self.indices(hash).all(|i| self.cells[i] > 0)
+    }
+
+    /// Returns the number of hashes per item.
+    #[inline]
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    /// Returns the number of cells.
+    #[inline]
+    pub fn num_cells(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns the value cells are set to on insert.
+    #[inline]
+    pub fn max_count(&self) -> u8 {
+        self.max
+    }
+
+    /// Returns the number of cells decremented per insert.
+    #[inline]
+    pub fn decay_count(&self) -> usize {
+        self.decay
+    }
+
+    /// Returns the false positive rate the Bloom filter converges to.
+    #[inline]
+    pub fn false_pos(&self) -> f64 {
+        self.fp
+    }
+
+    /// Clear all of the cells in the Bloom filter, removing all items.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.cells.iter_mut().for_each(|x| *x = 0);
+    }
+
+    #[inline]
+    fn source_hash(&self, val: &(impl Hash + ?Sized)) -> u64 {
+        let mut state = self.hasher.build_hasher();
+        val.hash(&mut state);
+        state.finish()
+    }
+
+    /// Returns the cell index of each of the item's hashes, probing like [`BloomFilter`](crate::BloomFilter) does.
+    #[inline]
+    fn indices(&self, hash: u64) -> impl Iterator<Item = usize> {
+        let num_cells = self.num_cells();
+        let mut hasher = DoubleHasher::new(hash);
+        core::iter::once(index(num_cells, hash))
+            .chain((1..self.num_hashes).map(move |_| index(num_cells, hasher.next())))
+    }
+
+    /// Decrements `decay` consecutive cells, wrapping around, starting at a pseudo-random cell.
+    #[inline]
+    fn decrement(&mut self) {
+        let num_cells = self.num_cells();
+        let start = index(num_cells, self.next_random());
+        for i in (start..num_cells).chain(0..start).take(self.decay) {
+            self.cells[i] = self.cells[i].saturating_sub(1);
+        }
+    }
+
+    /// Returns the next output of wyrand, a fast generator that's sufficient for picking cells.
+    #[inline]
+    fn next_random(&mut self) -> u64 {
+        self.rng = self.rng.wrapping_add(0xa076_1d64_78bd_642f);
+        let t = self.rng as u128 * (self.rng ^ 0xe703_7ed1_a0b4_28db) as u128;
+        (t >> 64) as u64 ^ t as u64
+    }
+}
+
+impl<T: Hash, S: BuildHasher> Extend<T> for StableBloomFilter<S> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.insert(&val);
+        }
+    }
+}
+
+/// Returns the number of cells to decrement per insert so that the false positive rate converges to `fp`.
+///
+/// In the limit, `fp = (1 - (1 / (1 + 1 / (decay * (1 / num_hashes - 1 / num_cells))))^max)^num_hashes`
+/// (Deng and Rafiei, Theorem 2), solved for `decay`. At least one cell is always decremented.
+fn decay(num_cells: usize, num_hashes: u32, max: u8, fp: f64) -> usize {
+    let a = 1.0 - crate::math::pow(fp, 1.0 / num_hashes as f64);
+    let b = crate::math::pow(a, -1.0 / max as f64) - 1.0;
+    let c = 1.0 / num_hashes as f64 - 1.0 / num_cells as f64;
+    let decay = crate::math::round(1.0 / (b * c));
+    (decay as usize).clamp(1, num_cells)
+}
+
+#[cfg(not(feature = "loom"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BloomFilter;
+
+    fn filter(num_bits: usize, num_hashes: u32, max: u8, fp: f64) -> StableBloomFilter {
+        BloomFilter::with_num_bits(num_bits)
+            .seed(&1)
+            .stable_hashes(num_hashes, max, fp)
+    }
+
+    #[test]
+    fn fp_converges() {
+        for (num_hashes, max, fp) in [(2, 1, 0.05), (3, 3, 0.01), (4, 7, 0.02)] {
+            let mut filter = filter(1 << 14, num_hashes, max, fp);
+            let num_items = 200_000u64;
+            filter.extend(0..num_items);
+
+            let trials = 100_000;
+            let false_positives = (num_items..num_items + trials)
+                .filter(|x| filter.contains(x))
+                .count();
+            let sample_fp = false_positives as f64 / trials as f64;
+            let err = (sample_fp - fp).abs() / fp;
+            assert!(err < 0.2, "sample fp: {sample_fp}, target: {fp}");
+        }
+    }
+
+    #[test]
+    fn recent_items_contained() {
+        let mut filter = filter(1 << 14, 3, 3, 0.01);
+        for x in 0..100_000u64 {
+            filter.insert(&x);
+            assert!(filter.contains(&x));
+        }
+        assert!((99_990..100_000u64).all(|x| filter.contains(&x)));
+        let num_old = (0..1000u64).filter(|x| filter.contains(x)).count();
+        assert!(num_old < 50, "{num_old}");
+    }
+
+    #[test]
+    fn decay_count() {
+        let filter = filter(1 << 14, 3, 3, 0.01);
+        assert_eq!(filter.decay_count(), decay(1 << 14, 3, 3, 0.01));
+        assert!(filter.decay_count() >= 1);
+        assert!(filter.decay_count() < filter.num_cells());
+        assert_eq!(filter.max_count(), 3);
+        assert_eq!(filter.false_pos(), 0.01);
+    }
+
+    #[test]
+    fn clear() {
+        let mut filter = filter(1024, 3, 3, 0.01);
+        filter.extend(0..10u64);
+        filter.clear();
+        assert!(filter.cells.iter().all(|&x| x == 0));
+        assert!((0..10u64).all(|x| !filter.contains(&x)));
+    }
+
+    #[test]
+    #[should_panic(expected = "max must be greater than 0")]
+    fn zero_max() {
+        let _ = filter(1024, 3, 0, 0.01);
+    }
+}